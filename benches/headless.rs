@@ -0,0 +1,114 @@
+//! Headless Classic-vs-Classic games per second:
+//! `cargo bench --bench headless`.
+//!
+//! `logged-with-names` keeps the per-action name allocation of the path
+//! before actions referred to players by position. The README records how the
+//! three cases compared against that path as a whole when actions were
+//! switched over.
+
+use criterion::{ criterion_group, criterion_main, Criterion, Throughput };
+use rand::{ Rng, SeedableRng, rngs::StdRng };
+use wheels::engine::{
+    builder::GameBuilder,
+    game::{ Action, ActionLog, Game, PlayerId, PlayerPos, HEADLESS_TURN_LIMIT },
+    hero::HeroKind,
+    strategy::Strategy,
+};
+
+fn new_game() -> Game {
+    GameBuilder::new(
+        (HeroKind::Warrior, HeroKind::Mage),
+        (HeroKind::Priest, HeroKind::Archer),
+    )
+    .build()
+    .unwrap()
+}
+
+/// Play a game as [`Game::run_headless`] does, but collecting a log of every
+/// turn.
+fn run_logged<R>(game: &mut Game, rng: &mut R) -> usize
+where R: Rng + ?Sized
+{
+    for turn in 1..=HEADLESS_TURN_LIMIT {
+        let p1 = game.get_rolls_strategy(PlayerPos::P1, Strategy::Classic, rng);
+        let p2 = game.get_rolls_strategy(PlayerPos::P2, Strategy::Classic, rng);
+        let (winner, log) = game.do_turn_rng(&p1, &p2, rng);
+        criterion::black_box(log);
+        if winner.is_some() { return turn; }
+    }
+    HEADLESS_TURN_LIMIT
+}
+
+/// An [`ActionLog`] that tags every action with an owned copy of the acting
+/// player's name, as actions were before they referred to players by
+/// position, to keep the cost of that allocation measurable.
+struct NamedLog {
+    names: [String; 2],
+    actions: Vec<(PlayerId, Action)>,
+}
+
+impl ActionLog for NamedLog {
+    fn push(&mut self, action: Action) {
+        use Action::*;
+        let pos
+            = match action {
+                ExpUp(pos, ..) | LevelUp(pos, ..) | BulwarkUp(pos, ..)
+                | EnergyAdd(pos, ..) | ExpCarry(pos, ..)
+                | EnergyCarry(pos, ..) | AssassinDamage(pos, ..)
+                | AssassinDelay(pos, ..) | PriestHeal(pos, ..)
+                | PriestEnergy(pos, ..) | EngineerBuild(pos, ..)
+                | HeroDamage(pos, ..) | Bomb(pos, ..) => pos,
+            };
+        let id
+            = match pos {
+                PlayerPos::P1 => PlayerId::P1(self.names[0].clone()),
+                PlayerPos::P2 => PlayerId::P2(self.names[1].clone()),
+            };
+        self.actions.push((id, action));
+    }
+}
+
+/// Like [`run_logged`], but collecting a [`NamedLog`] of every turn.
+fn run_named<R>(game: &mut Game, rng: &mut R) -> usize
+where R: Rng + ?Sized
+{
+    let names = [PlayerPos::P1, PlayerPos::P2]
+        .map(|pos| game.get_player(pos).get_name().to_string());
+    let mut log = NamedLog { names, actions: Vec::new() };
+    for turn in 1..=HEADLESS_TURN_LIMIT {
+        let p1 = game.get_rolls_strategy(PlayerPos::P1, Strategy::Classic, rng);
+        let p2 = game.get_rolls_strategy(PlayerPos::P2, Strategy::Classic, rng);
+        let winner
+            = game.do_turn_partial_log(Some(&p1), Some(&p2), rng, &mut log);
+        // a new log every turn, as before
+        criterion::black_box(std::mem::take(&mut log.actions));
+        if winner.is_some() { return turn; }
+    }
+    HEADLESS_TURN_LIMIT
+}
+
+fn headless_games(c: &mut Criterion) {
+    let game = new_game();
+    let mut group = c.benchmark_group("games");
+    // report games per second
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("logged-with-names", |b| {
+        let mut rng = StdRng::seed_from_u64(0);
+        b.iter(|| run_named(&mut game.clone(), &mut rng))
+    });
+    group.bench_function("logged", |b| {
+        let mut rng = StdRng::seed_from_u64(0);
+        b.iter(|| run_logged(&mut game.clone(), &mut rng))
+    });
+    group.bench_function("unlogged", |b| {
+        let mut rng = StdRng::seed_from_u64(0);
+        b.iter(|| {
+            game.clone()
+                .run_headless(Strategy::Classic, Strategy::Classic, &mut rng)
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, headless_games);
+criterion_main!(benches);
@@ -0,0 +1,403 @@
+use crate::engine::{
+    hero::Hero,
+    locks::fmt_command,
+    player::{ Player, HeroPos, MAX_BULWARK },
+    wheel::{ Wheel, WheelKind, Rolls, NUM_FACES },
+};
+
+/// Score given to each expected point of bulwark gained.
+const BULWARK_WEIGHT: f64 = 0.5;
+
+/// Score given to each expected point of EXP gained.
+const EXP_WEIGHT: f64 = 0.25;
+
+/// Expected outcome of a turn for a single player, given a choice of locks.
+#[derive(Copy, Clone, Debug)]
+pub struct Outlook {
+    /// Probability that the left hero fills its rod this turn.
+    pub act_l: f64,
+    /// Probability that the right hero fills its rod this turn.
+    pub act_r: f64,
+    /// Expected bulwark gain.
+    pub bulwark: f64,
+    /// Expected panel EXP for the left hero.
+    pub exp_l: f64,
+    /// Expected panel EXP for the right hero.
+    pub exp_r: f64,
+    /// Overall desirability of the outcome, used to rank lock choices.
+    pub score: f64,
+}
+
+/// A recommended set of locks.
+#[derive(Clone, Debug)]
+pub struct Advice {
+    /// Which wheels to lock.
+    pub locks: [bool; 5],
+    /// Expected outcome of locking `locks`.
+    pub outlook: Outlook,
+    /// Short explanation, e.g. "Warrior needs 2 more squares".
+    pub reason: String,
+}
+
+impl Advice {
+    /// Describe the advice as what to type at the lock prompt to get from
+    /// `locks` to the advised locks, followed by the reason.
+    pub fn describe(&self, locks: &[bool; 5]) -> String {
+        let command
+            = match fmt_command(locks, &self.locks) {
+                command if command.is_empty() => "press ENTER".to_string(),
+                command => format!("enter '{}'", command),
+            };
+        format!("{} (locks {}): {}",
+            command, fmt_locks(&self.locks), self.reason)
+    }
+}
+
+/// Rough worth of a single action taken by `hero`.
+fn act_value(hero: &Hero) -> f64 {
+    let crown_dmg
+        = if hero.is_mage() {
+            2 * hero.get_crown_dmg()
+        } else {
+            hero.get_crown_dmg()
+        };
+    f64::from(
+        crown_dmg
+        + hero.get_bulwark_dmg()
+        + hero.get_crown_heal()
+        + hero.get_bulwark_heal()
+        + hero.get_delay()
+        + hero.get_energy_gen()
+    )
+}
+
+/// Distribution over the final panel of wheel `k` after `spins_left` spins,
+/// where the first spin re-rolls the wheel if it isn't locked and every later
+/// spin re-rolls it if its panel isn't one of `keep`.
+pub(crate) fn wheel_dist(
+    k: usize,
+    wheel: Wheel,
+    lock: bool,
+    keep: &[WheelKind],
+    spins_left: usize,
+) -> Vec<(Wheel, f64)>
+{
+    if lock || spins_left == 0 { return vec![(wheel, 1.0)]; }
+    let uniform: f64 = 1.0 / f64::from(NUM_FACES);
+    let faces: Vec<Wheel>
+        = (0..NUM_FACES).map(|face| Wheel::sides(k, face)).collect();
+    let mut probs: Vec<f64> = vec![uniform; faces.len()];
+    for _ in 1..spins_left {
+        let respin: f64
+            = faces.iter().zip(probs.iter())
+            .filter(|(w, _)| !keep.contains(&w.get_kind()))
+            .map(|(_, p)| *p)
+            .sum();
+        faces.iter().zip(probs.iter_mut())
+            .for_each(|(w, p)| {
+                if keep.contains(&w.get_kind()) {
+                    *p += respin * uniform;
+                } else {
+                    *p = respin * uniform;
+                }
+            });
+    }
+    faces.into_iter().zip(probs).collect()
+}
+
+/// Distribution over the total of `count` across all wheels.
+pub(crate) fn total_dist(
+    dists: &[Vec<(Wheel, f64)>],
+    count: fn(&Wheel) -> Option<u8>,
+) -> Vec<f64>
+{
+    let mut total: Vec<f64> = vec![1.0];
+    for dist in dists.iter() {
+        let max: usize
+            = dist.iter()
+            .map(|(w, _)| count(w).unwrap_or(0) as usize)
+            .max()
+            .unwrap_or(0);
+        let mut new: Vec<f64> = vec![0.0; total.len() + max];
+        for (n, pn) in total.iter().enumerate() {
+            for (w, pw) in dist.iter() {
+                new[n + count(w).unwrap_or(0) as usize] += pn * pw;
+            }
+        }
+        total = new;
+    }
+    total
+}
+
+/// Expected total of `count` across all wheels.
+pub(crate) fn total_mean(
+    dists: &[Vec<(Wheel, f64)>],
+    count: fn(&Wheel) -> Option<u8>,
+) -> f64
+{
+    dists.iter()
+        .flat_map(|dist| dist.iter())
+        .map(|(w, p)| f64::from(count(w).unwrap_or(0)) * p)
+        .sum()
+}
+
+/// Probability that a hero with `energy_left` fills its rod from a
+/// distribution over total energy panels.
+fn act_prob(dist: &[f64], energy_left: u8) -> f64 {
+    let needed: usize = (energy_left as usize + 2).max(3);
+    dist.iter().skip(needed).fold(0.0, |acc, p| acc + p)
+}
+
+fn outlook_keep(
+    player: &Player,
+    rolls: &Rolls,
+    locks: &[bool; 5],
+    keep: &[WheelKind],
+    spins_left: usize,
+) -> Outlook
+{
+    let hero_l = player.get_hero(HeroPos::L);
+    let hero_r = player.get_hero(HeroPos::R);
+    let dists: Vec<Vec<(Wheel, f64)>>
+        = rolls.iter().zip(locks.iter()).enumerate()
+        .map(|(k, (wheel, lock))| {
+            wheel_dist(k, *wheel, *lock, keep, spins_left)
+        })
+        .collect();
+    let act_l: f64
+        = act_prob(
+            &total_dist(&dists, Wheel::square_energy),
+            hero_l.get_energy_left(),
+        );
+    let act_r: f64
+        = act_prob(
+            &total_dist(&dists, Wheel::diamond_energy),
+            hero_r.get_energy_left(),
+        );
+    let room: usize = MAX_BULWARK.saturating_sub(player.get_bulwark()).into();
+    let bulwark: f64
+        = total_dist(&dists, Wheel::hammer_energy).into_iter()
+        .enumerate()
+        .map(|(n, p)| n.saturating_sub(2).min(room) as f64 * p)
+        .sum();
+    let exp_l: f64 = total_mean(&dists, Wheel::square_exp);
+    let exp_r: f64 = total_mean(&dists, Wheel::diamond_exp);
+    let score: f64
+        = act_l * act_value(hero_l)
+        + act_r * act_value(hero_r)
+        + bulwark * BULWARK_WEIGHT
+        + (exp_l + exp_r) * EXP_WEIGHT;
+    Outlook { act_l, act_r, bulwark, exp_l, exp_r, score }
+}
+
+/// Evaluate the outcome of locking `locks` on `rolls` with `spins_left`
+/// spins remaining in the turn.
+///
+/// Unlocked wheels are re-rolled as in [`Wheel::gen_rolls_locked`]. For any
+/// spins after the next one, each wheel is assumed to be kept if it lands on
+/// one of a fixed set of panel kinds; the best such set is used.
+pub fn evaluate(
+    player: &Player,
+    rolls: &Rolls,
+    locks: &[bool; 5],
+    spins_left: usize,
+) -> Outlook
+{
+    use WheelKind::*;
+    let keeps: [&[WheelKind]; 8] = [
+        &[],
+        &[Square],
+        &[Diamond],
+        &[Hammer],
+        &[Square, Diamond],
+        &[Square, Hammer],
+        &[Diamond, Hammer],
+        &[Square, Diamond, Hammer],
+    ];
+    if spins_left > 1 {
+        keeps.iter()
+            .map(|keep| outlook_keep(player, rolls, locks, keep, spins_left))
+            .max_by(|l, r| l.score.total_cmp(&r.score))
+            .unwrap()
+    } else {
+        outlook_keep(player, rolls, locks, &[], spins_left)
+    }
+}
+
+/// Find the set of locks on `rolls` with the best [`Outlook`] for `player`
+/// with `spins_left` spins remaining in the turn.
+pub fn advise(player: &Player, rolls: &Rolls, spins_left: usize) -> Advice {
+    advise_locked(player, rolls, spins_left, &[false; 5])
+}
+
+/// Like [`advise`], but only considering sets of locks that keep every wheel
+/// in `locked` locked.
+pub fn advise_locked(
+    player: &Player,
+    rolls: &Rolls,
+    spins_left: usize,
+    locked: &[bool; 5],
+) -> Advice
+{
+    let (locks, outlook)
+        = (0..32_u8)
+        .map(|mask| {
+            let mut locks = [false; 5];
+            locks.iter_mut().enumerate()
+                .for_each(|(k, lock)| { *lock = mask & (1 << k) != 0; });
+            locks
+        })
+        .filter(|locks| {
+            locks.iter().zip(locked.iter()).all(|(lock, l)| *lock || !*l)
+        })
+        .map(|locks| (locks, evaluate(player, rolls, &locks, spins_left)))
+        .fold(None, |best: Option<([bool; 5], Outlook)>, (locks, outlook)| {
+            match best {
+                Some((_, b)) if b.score >= outlook.score => best,
+                _ => Some((locks, outlook)),
+            }
+        })
+        .unwrap();
+    let reason = explain(player, rolls, &locks, &outlook);
+    Advice { locks, outlook, reason }
+}
+
+/// Render a set of locks as a list of 1-indexed wheel numbers.
+pub fn fmt_locks(locks: &[bool; 5]) -> String {
+    let nums: Vec<String>
+        = locks.iter().enumerate()
+        .filter(|(_, lock)| **lock)
+        .map(|(k, _)| (k + 1).to_string())
+        .collect();
+    if nums.is_empty() { "none".to_string() } else { nums.join(",") }
+}
+
+fn plural(n: usize, word: &str) -> String {
+    if n == 1 { word.to_string() } else { format!("{}s", word) }
+}
+
+fn explain(player: &Player, rolls: &Rolls, locks: &[bool; 5], outlook: &Outlook)
+    -> String
+{
+    let hero_l = player.get_hero(HeroPos::L);
+    let hero_r = player.get_hero(HeroPos::R);
+    let locked = |count: fn(&Wheel) -> Option<u8>| -> usize {
+        rolls.iter().zip(locks.iter())
+            .filter(|(_, lock)| **lock)
+            .filter_map(|(w, _)| count(w))
+            .map(usize::from)
+            .sum()
+    };
+    let needs = |hero: &Hero, panel: &str, have: usize| -> String {
+        let needed: usize = (hero.get_energy_left() as usize + 2).max(3);
+        match needed.saturating_sub(have) {
+            0 => format!("{} already has enough {}s", hero.get_kind(), panel),
+            n => format!(
+                "{} needs {} more {}", hero.get_kind(), n, plural(n, panel)),
+        }
+    };
+    let contrib_l = outlook.act_l * act_value(hero_l);
+    let contrib_r = outlook.act_r * act_value(hero_r);
+    let contrib_b = outlook.bulwark * BULWARK_WEIGHT;
+    if contrib_l <= 0.0 && contrib_r <= 0.0 && contrib_b <= 0.0 {
+        "nothing is worth keeping".to_string()
+    } else if contrib_l >= contrib_r && contrib_l >= contrib_b {
+        needs(hero_l, "square", locked(Wheel::square_energy))
+    } else if contrib_r >= contrib_b {
+        needs(hero_r, "diamond", locked(Wheel::diamond_energy))
+    } else {
+        match 3_usize.saturating_sub(locked(Wheel::hammer_energy)) {
+            0 => "Bulwark already has enough hammers".to_string(),
+            n => format!(
+                "Bulwark needs {} more {}", n, plural(n, "hammer")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::{
+        hero::HeroKind,
+        locks,
+        wheel::parse_rolls,
+    };
+    use super::*;
+
+    /// A Warrior and a Priest each one energy short of acting, behind a full
+    /// Bulwark.
+    fn player() -> Player {
+        let mut player
+            = Player::new(
+                "",
+                Hero::from_kind(HeroKind::Warrior),
+                Hero::from_kind(HeroKind::Priest),
+            );
+        player.set_bulwark(MAX_BULWARK).unwrap();
+        for pos in [HeroPos::L, HeroPos::R] {
+            let hero = player.get_hero_mut(pos);
+            hero.set_energy(hero.get_rod_len() - 1).unwrap();
+        }
+        player
+    }
+
+    #[test]
+    fn lock_what_guarantees_acting() {
+        let player = player();
+        // each hero needs 3 of its symbol, which wheels 1 and 2 alone give;
+        // hammers are worth nothing with a full Bulwark, so wheels 3-5 are
+        // better spun again for their chance at EXP
+        let rolls = parse_rolls("SSS* DDD* HHH HHH HHH").unwrap();
+        let advice = advise(&player, &rolls, 1);
+        assert_eq!(advice.locks, [true, true, false, false, false]);
+
+        // the locked wheels give 1 EXP each, and a re-spin of wheels 3-5
+        // gives 1/8 + 1/8 + 2/8 expected EXP to each hero
+        let outlook = advice.outlook;
+        assert_eq!((outlook.act_l, outlook.act_r), (1.0, 1.0));
+        assert_eq!(outlook.bulwark, 0.0);
+        assert_eq!((outlook.exp_l, outlook.exp_r), (1.5, 1.5));
+        let hero = |pos: HeroPos| player.get_hero(pos);
+        let score
+            = act_value(hero(HeroPos::L)) + act_value(hero(HeroPos::R))
+            + 3.0 * EXP_WEIGHT;
+        assert_eq!(outlook.score, score);
+        assert_eq!(advice.reason, "Warrior already has enough squares");
+        assert_eq!(advice.describe(&[true, false, true, false, false]),
+            "enter '+2 -3' (locks 1,2): Warrior already has enough squares");
+        assert!(advice.describe(&advice.locks).starts_with("press ENTER"));
+    }
+
+    #[test]
+    fn no_spins_left() {
+        // with nothing left to spin, every choice is the same, so lock none
+        let player = player();
+        let rolls = parse_rolls("SSS* DDD* HHH HHH HHH").unwrap();
+        assert_eq!(advise(&player, &rolls, 0).locks, [false; 5]);
+        let locked = [false, false, true, false, false];
+        let advice = advise_locked(&player, &rolls, 0, &locked);
+        assert_eq!(advice.locks, locked);
+    }
+
+    #[test]
+    fn advice_command_reaches_advised_locks() {
+        let player = player();
+        let rolls = parse_rolls("SS DD* HH S* D").unwrap();
+        for mask in 0..32_u8 {
+            let current = [0, 1, 2, 3, 4].map(|k| mask & (1 << k) != 0);
+            for permanent in [false, true] {
+                let locked = if permanent { current } else { [false; 5] };
+                let advice = advise_locked(&player, &rolls, 2, &locked);
+                // what the player is told to type, if anything
+                let line = advice.describe(&current);
+                let command
+                    = line.strip_prefix("enter '")
+                    .and_then(|rest| rest.split_once('\''))
+                    .map_or("", |(command, _)| command);
+                let mut locks = current;
+                locks::apply(command, &rolls, &mut locks, permanent)
+                    .unwrap();
+                assert_eq!(locks, advice.locks, "{}", command);
+            }
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use crate::engine::{
+    advisor::{ total_dist, total_mean, wheel_dist },
+    handicap::RESPINS,
+    hero::{ Hero, HeroKind, MAX_LEVEL },
+    player::HeroPos,
+    wheel::{ Wheel, WheelKind },
+};
+
+/// How wheels are locked between spins when computing a [`BalanceSheet`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LockPolicy {
+    /// Never lock anything, so that every panel is a fresh spin.
+    Idle,
+    /// Lock every wheel showing the panel kind that feeds the hero's side:
+    /// squares for the left hero and diamonds for the right.
+    Feed,
+}
+
+impl LockPolicy {
+    /// All lock policies.
+    pub fn all() -> [Self; 2] { [Self::Idle, Self::Feed] }
+
+    /// Look up a lock policy by its (case-insensitive) name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::all().into_iter()
+            .find(|policy| policy.to_string().eq_ignore_ascii_case(name.trim()))
+    }
+}
+
+impl std::fmt::Display for LockPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        format!("{:?}", self).fmt(f)
+    }
+}
+
+/// The panel kind feeding energy and EXP to the hero at `side`.
+fn feed_kind(side: HeroPos) -> WheelKind {
+    match side {
+        HeroPos::L => WheelKind::Square,
+        HeroPos::R => WheelKind::Diamond,
+    }
+}
+
+/// Expected output of a single hero per turn, from panels alone.
+///
+/// Damage assumes every hit lands on the Crown of an opponent without
+/// Bulwark, or on the Bulwark of an opponent with enough of it to absorb the
+/// hit, respectively.
+#[derive(Copy, Clone, Debug)]
+pub struct Throughput {
+    pub kind: HeroKind,
+    pub level: u8,
+    pub side: HeroPos,
+    pub rod_len: u8,
+    /// Expected number of turns to fill an empty rod.
+    pub turns_to_act: f64,
+    /// Long-run average number of acts per turn.
+    pub acts: f64,
+    pub crown_dmg: f64,
+    pub bulwark_dmg: f64,
+    /// Crown healed.
+    pub heal: f64,
+    /// Bulwark built.
+    pub build: f64,
+    /// Energy removed from an opposing hero.
+    pub delay: f64,
+    /// Energy given to the other hero.
+    pub energy_gen: f64,
+    /// EXP from panels and from acting.
+    pub exp: f64,
+}
+
+impl Throughput {
+    /// Compute the throughput of `hero` at `side`, locking by `policy` with
+    /// `respins` re-spins per turn.
+    pub fn new(
+        hero: &Hero,
+        side: HeroPos,
+        policy: LockPolicy,
+        respins: usize,
+        carry_over: bool,
+    ) -> Self
+    {
+        let kind = feed_kind(side);
+        let keep: &[WheelKind]
+            = match policy {
+                LockPolicy::Idle => &[],
+                LockPolicy::Feed => &[kind],
+            };
+        let dists: Vec<Vec<(Wheel, f64)>>
+            = (0..5)
+            .map(|k| {
+                wheel_dist(k, Wheel::sides(k, 0), false, keep, respins + 1)
+            })
+            .collect();
+        let (energy, panel_exp)
+            = match side {
+                HeroPos::L => (
+                    total_dist(&dists, Wheel::square_energy),
+                    total_mean(&dists, Wheel::square_exp),
+                ),
+                HeroPos::R => (
+                    total_dist(&dists, Wheel::diamond_energy),
+                    total_mean(&dists, Wheel::diamond_exp),
+                ),
+            };
+        // only panels beyond the first two give energy
+        let mut gain: Vec<f64> = vec![0.0; energy.len().saturating_sub(2)];
+        energy.iter().enumerate()
+            .for_each(|(n, p)| { gain[n.saturating_sub(2)] += p; });
+
+        let rod_len = hero.get_rod_len();
+        let turns_to_act = turns_to_fill(&gain, rod_len);
+        let acts
+            = if carry_over {
+                let mean: f64
+                    = gain.iter().enumerate()
+                    .map(|(n, p)| n as f64 * p)
+                    .sum();
+                mean / f64::from(rod_len)
+            } else {
+                turns_to_act.recip()
+            };
+        let hits: u8 = if hero.is_mage() { 2 } else { 1 };
+        let per_act = |x: u8| acts * f64::from(x);
+        Self {
+            kind: hero.get_kind(),
+            level: hero.get_level(),
+            side,
+            rod_len,
+            turns_to_act,
+            acts,
+            crown_dmg: per_act(hits * hero.get_crown_dmg()),
+            // a Mage's second hit always goes to the Crown
+            bulwark_dmg: per_act(hero.get_bulwark_dmg()),
+            heal: per_act(hero.get_crown_heal()),
+            build: per_act(hero.get_bulwark_heal()),
+            delay: per_act(hero.get_delay()),
+            energy_gen: per_act(hero.get_energy_gen()),
+            exp: panel_exp + per_act(2),
+        }
+    }
+}
+
+/// Expected number of turns to fill an empty rod of length `rod_len`, where
+/// `gain[n]` is the probability of gaining `n` energy in a turn.
+fn turns_to_fill(gain: &[f64], rod_len: u8) -> f64 {
+    let stay = gain.first().copied().unwrap_or(1.0);
+    if stay >= 1.0 { return f64::INFINITY; }
+    // turns[e] is the expected number of turns to fill the rod from `e`
+    let rod_len = rod_len as usize;
+    let mut turns: Vec<f64> = vec![0.0; rod_len];
+    for e in (0..rod_len).rev() {
+        let rest: f64
+            = gain.iter().enumerate()
+            .skip(1)
+            .filter(|(n, _)| e + n < rod_len)
+            .map(|(n, p)| p * turns[e + n])
+            .sum();
+        turns[e] = (1.0 + rest) / (1.0 - stay);
+    }
+    turns[0]
+}
+
+/// The [`Throughput`] of every hero at every level on each side, for
+/// comparing stat tables.
+#[derive(Clone, Debug)]
+pub struct BalanceSheet {
+    pub policy: LockPolicy,
+    pub respins: usize,
+    pub carry_over: bool,
+    pub rows: Vec<Throughput>,
+}
+
+impl BalanceSheet {
+    pub fn new(policy: LockPolicy, respins: usize, carry_over: bool) -> Self {
+        let mut rows: Vec<Throughput> = Vec::new();
+        for kind in HeroKind::all() {
+            for level in 0..=MAX_LEVEL {
+                let mut hero = Hero::from_kind(kind);
+                hero.set_level(level).unwrap();
+                for side in [HeroPos::L, HeroPos::R] {
+                    rows.push(Throughput::new(
+                        &hero, side, policy, respins, carry_over));
+                }
+            }
+        }
+        Self { policy, respins, carry_over, rows }
+    }
+}
+
+impl Default for BalanceSheet {
+    fn default() -> Self { Self::new(LockPolicy::Feed, RESPINS, false) }
+}
+
+/// One line per hero, level and side, so that sheets for two versions of the
+/// stat tables can be compared with `diff`.
+impl std::fmt::Display for BalanceSheet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "# policy={} respins={} carry-over={}",
+            self.policy, self.respins, self.carry_over)?;
+        writeln!(f, "# {:<8} {:>3} {:>4} {:>3} {:>6} {:>6} {:>6} {:>6} \
+            {:>6} {:>6} {:>6} {:>6} {:>6}",
+            "hero", "lvl", "side", "rod", "turns", "acts", "crown", "bulwk",
+            "heal", "build", "delay", "energy", "exp")?;
+        for row in self.rows.iter() {
+            let side = if row.side == HeroPos::L { "L" } else { "R" };
+            writeln!(f, "  {:<8} {:>3} {:>4} {:>3} {:>6.3} {:>6.3} {:>6.3} \
+                {:>6.3} {:>6.3} {:>6.3} {:>6.3} {:>6.3} {:>6.3}",
+                row.kind.to_string(), row.level, side, row.rod_len,
+                row.turns_to_act, row.acts, row.crown_dmg, row.bulwark_dmg,
+                row.heal, row.build, row.delay, row.energy_gen, row.exp)?;
+        }
+        Ok(())
+    }
+}
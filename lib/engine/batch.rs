@@ -0,0 +1,203 @@
+use std::{
+    io::{ self, Write },
+    sync::atomic::{ AtomicUsize, Ordering },
+    time::Instant,
+};
+use rayon::prelude::*;
+use crate::engine::{
+    game::{ EndReason, Winner },
+    rules::Rules,
+    tournament::{ Entrant, Tournament, game_seed },
+};
+
+/// Number of progress updates printed over a batch.
+const PROGRESS_STEPS: usize = 100;
+
+/// Totals over a batch of games.
+///
+/// Every field is a sum over games, so results are the same whichever order
+/// the games are played in.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct BatchResults {
+    pub p1_wins: usize,
+    pub p2_wins: usize,
+    pub draws: usize,
+    /// Games decided by the turn limit of the rules.
+    pub turn_limit: usize,
+    /// Games called off after [`HEADLESS_TURN_LIMIT`] turns.
+    ///
+    /// [`HEADLESS_TURN_LIMIT`]: crate::engine::game::HEADLESS_TURN_LIMIT
+    pub abandoned: usize,
+    /// Total number of turns over all games.
+    pub turns: usize,
+}
+
+impl BatchResults {
+    pub fn games(&self) -> usize { self.p1_wins + self.p2_wins + self.draws }
+
+    /// Average number of turns per game.
+    pub fn mean_turns(&self) -> f64 {
+        self.turns as f64 / self.games().max(1) as f64
+    }
+
+    fn add(mut self, (winner, turns): (Winner, usize)) -> Self {
+        match winner {
+            Winner::P1(_) => { self.p1_wins += 1; },
+            Winner::P2(_) => { self.p2_wins += 1; },
+            Winner::Draw(_) => { self.draws += 1; },
+        }
+        match winner.reason() {
+            EndReason::Crown => { },
+            EndReason::TurnLimit(_) => { self.turn_limit += 1; },
+            EndReason::Abandoned => { self.abandoned += 1; },
+        }
+        self.turns += turns;
+        self
+    }
+
+    fn merge(self, other: Self) -> Self {
+        Self {
+            p1_wins: self.p1_wins + other.p1_wins,
+            p2_wins: self.p2_wins + other.p2_wins,
+            draws: self.draws + other.draws,
+            turn_limit: self.turn_limit + other.turn_limit,
+            abandoned: self.abandoned + other.abandoned,
+            turns: self.turns + other.turns,
+        }
+    }
+}
+
+impl std::fmt::Display for BatchResults {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let games = self.games().max(1) as f64;
+        let pct = |n: usize| 100.0 * n as f64 / games;
+        writeln!(f, "games:      {}", self.games())?;
+        writeln!(f, "P1 wins:    {} ({:.2}%)",
+            self.p1_wins, pct(self.p1_wins))?;
+        writeln!(f, "P2 wins:    {} ({:.2}%)",
+            self.p2_wins, pct(self.p2_wins))?;
+        writeln!(f, "draws:      {} ({:.2}%)", self.draws, pct(self.draws))?;
+        writeln!(f, "turn limit: {} ({:.2}%)",
+            self.turn_limit, pct(self.turn_limit))?;
+        writeln!(f, "abandoned:  {} ({:.2}%)",
+            self.abandoned, pct(self.abandoned))?;
+        writeln!(f, "mean turns: {:.3}", self.mean_turns())?;
+        Ok(())
+    }
+}
+
+/// Reports how many of a batch's games have been played on stderr, about
+/// [`PROGRESS_STEPS`] times over the batch.
+#[derive(Debug)]
+pub(crate) struct Progress {
+    total: usize,
+    step: usize,
+    done: AtomicUsize,
+    start: Instant,
+}
+
+impl Progress {
+    pub(crate) fn new(total: usize) -> Self {
+        Self {
+            total,
+            step: total.div_ceil(PROGRESS_STEPS).max(1),
+            done: AtomicUsize::new(0),
+            start: Instant::now(),
+        }
+    }
+
+    /// Count a finished game.
+    pub(crate) fn tick(&self) {
+        let done = self.done.fetch_add(1, Ordering::Relaxed) + 1;
+        if done.is_multiple_of(self.step) || done == self.total {
+            let secs = self.start.elapsed().as_secs_f64();
+            eprint!("\r{} / {} games ({:.0}%, {:.0} games/s)",
+                done,
+                self.total,
+                100.0 * done as f64 / self.total as f64,
+                done as f64 / secs.max(1e-9),
+            );
+            io::stderr().flush().ok();
+        }
+    }
+
+    pub(crate) fn finish(&self) { eprintln!(); }
+}
+
+/// Many games between the same two entrants, played in parallel.
+///
+/// Game `n` is played with an RNG seeded from [`game_seed`]`(seed, n)`, so
+/// results depend only on the master seed, never on the number of threads.
+#[derive(Clone, Debug)]
+pub struct Batch {
+    p1: Entrant,
+    p2: Entrant,
+    games: usize,
+    seed: u64,
+    rules: Rules,
+    threads: Option<usize>,
+    progress: bool,
+}
+
+impl Batch {
+    pub fn new(p1: Entrant, p2: Entrant, games: usize, seed: u64, rules: Rules)
+        -> Self
+    {
+        Self { p1, p2, games, seed, rules, threads: None, progress: false }
+    }
+
+    /// Play on `threads` threads instead of one per core.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads);
+        self
+    }
+
+    /// Report progress on stderr while playing.
+    pub fn progress(mut self, progress: bool) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Play every game.
+    pub fn run(&self) -> Result<BatchResults, String> {
+        let progress = self.progress.then(|| Progress::new(self.games));
+        let play = || {
+            (0..self.games).into_par_iter()
+                .map(|n| {
+                    let seed = game_seed(self.seed, n as u64);
+                    let result
+                        = Tournament::play(
+                            &self.p1, &self.p2, seed, self.rules);
+                    if let Some(progress) = progress.as_ref() {
+                        progress.tick();
+                    }
+                    result
+                })
+                .fold(BatchResults::default, BatchResults::add)
+                .reduce(BatchResults::default, BatchResults::merge)
+        };
+        let results = run_on(self.threads, play)?;
+        if let Some(progress) = progress.as_ref() { progress.finish(); }
+        Ok(results)
+    }
+}
+
+/// Call `f` on a thread pool of `threads` threads, or on the global pool with
+/// one per core.
+pub(crate) fn run_on<T, F>(threads: Option<usize>, f: F) -> Result<T, String>
+where
+    T: Send,
+    F: FnOnce() -> T + Send,
+{
+    match threads {
+        Some(threads) => {
+            let pool
+                = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .map_err(|e| format!("error starting threads: {}", e))?;
+            Ok(pool.install(f))
+        },
+        None => Ok(f()),
+    }
+}
@@ -0,0 +1,118 @@
+use crate::engine::{
+    advisor::{ self, Advice, Outlook },
+    game::PlayerPos,
+    replay::{ Replay, Spin },
+};
+
+/// Default loss in expected score beyond which a choice of locks is flagged.
+pub const BLUNDER_THRESHOLD: f64 = 0.5;
+
+/// A choice of locks that lost significant expected value.
+#[derive(Clone, Debug)]
+pub struct Blunder {
+    pub turn: usize,
+    pub player: PlayerPos,
+    /// The rolls and locks actually chosen.
+    pub spin: Spin,
+    /// Expected outcome of the locks actually chosen.
+    pub chosen: Outlook,
+    /// The best available locks.
+    pub best: Advice,
+}
+
+impl Blunder {
+    /// Expected score lost relative to the best available locks.
+    pub fn loss(&self) -> f64 { self.best.outlook.score - self.chosen.score }
+}
+
+/// Results of reviewing every lock decision made by a player.
+#[derive(Clone, Debug)]
+pub struct Review {
+    /// Number of decisions reviewed.
+    pub reviewed: usize,
+    /// Total expected score lost over all decisions.
+    pub total_loss: f64,
+    /// Decisions that lost more than the threshold, in order.
+    pub blunders: Vec<Blunder>,
+}
+
+/// Compare each of `player`'s lock decisions in `replay` against those
+/// recommended by [`advisor::advise`], flagging any that lost more than
+/// `threshold` in expected score.
+///
+/// Under permanent locks, only choices that keep the previous spin's locks
+/// are considered.
+pub fn review(replay: &Replay, player: PlayerPos, threshold: f64) -> Review {
+    let mut reviewed: usize = 0;
+    let mut total_loss: f64 = 0.0;
+    let mut blunders: Vec<Blunder> = Vec::new();
+    for turn in replay.turns.iter() {
+        let record = turn.get(player);
+        let mut locked = [false; 5];
+        for spin in record.spins.iter() {
+            let chosen
+                = advisor::evaluate(
+                    &record.state, &spin.rolls, &spin.locks, spin.spins_left);
+            let best
+                = advisor::advise_locked(
+                    &record.state, &spin.rolls, spin.spins_left, &locked);
+            if replay.rules.permanent_locks { locked = spin.locks; }
+            let blunder = Blunder {
+                turn: turn.turn,
+                player,
+                spin: *spin,
+                chosen,
+                best,
+            };
+            reviewed += 1;
+            total_loss += blunder.loss().max(0.0);
+            if blunder.loss() > threshold {
+                blunders.push(blunder);
+            }
+        }
+    }
+    Review { reviewed, total_loss, blunders }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A Warrior and a Priest each one energy short of acting behind a full
+    /// Bulwark, keeping wheel 1 locked through a turn even though it ends up
+    /// showing a worthless hammer.
+    const REPLAY: &str = "\
+wheels-replay
+player P1 human Ann
+player P2 cpu CPU
+rules RULES
+turn 1
+state P1 crown=10 bulwark=5
+hero P1 L Warrior level=0 exp=0 energy=2 act=false
+hero P1 R Priest level=0 exp=0 energy=3 act=false
+state P2 crown=10 bulwark=0
+hero P2 L Mage level=0 exp=0 energy=0 act=false
+hero P2 R Archer level=0 exp=0 energy=0 act=false
+spin P1 2 H D S H S lock 1
+spin P1 1 H SSS* DDD* HHH HHH lock 1,2,3
+";
+
+    fn review_under(rules: &str) -> Review {
+        let replay: Replay = REPLAY.replace("RULES", rules).parse().unwrap();
+        review(&replay, PlayerPos::P1, 0.0)
+    }
+
+    #[test]
+    fn permanent_locks_limit_the_alternatives() {
+        // wheel 1 would be better spun again, if it could be unlocked
+        let free = review_under("standard");
+        let last = free.blunders.last().unwrap();
+        assert_eq!(last.spin.spins_left, 1);
+        assert_eq!(last.best.locks, [false, true, true, false, false]);
+
+        let permanent = review_under("permanent-locks");
+        assert_eq!(permanent.reviewed, 2);
+        assert!(permanent.blunders.iter().all(|b| b.spin.spins_left == 2));
+        assert!(permanent.total_loss < free.total_loss);
+    }
+}
@@ -0,0 +1,238 @@
+use crate::engine::{
+    game::{ Game, PlayerPos },
+    hero::{ Hero, HeroKind },
+    player::{ Player, HeroPos, INIT_CROWN, INIT_BULWARK },
+    rules::Rules,
+    validate::validate_player,
+};
+
+/// A player as specified to a [`GameBuilder`].
+#[derive(Clone, Debug)]
+struct PlayerSpec {
+    name: String,
+    crown: u8,
+    bulwark: u8,
+    hero_l: Hero,
+    hero_r: Hero,
+}
+
+impl PlayerSpec {
+    fn new(name: &str, heroes: (HeroKind, HeroKind)) -> Self {
+        Self {
+            name: name.to_string(),
+            crown: INIT_CROWN,
+            bulwark: INIT_BULWARK,
+            hero_l: Hero::from_kind(heroes.0),
+            hero_r: Hero::from_kind(heroes.1),
+        }
+    }
+
+    fn get_hero_mut(&mut self, pos: HeroPos) -> &mut Hero {
+        match pos {
+            HeroPos::L => &mut self.hero_l,
+            HeroPos::R => &mut self.hero_r,
+        }
+    }
+
+    /// Build the player, along with a description of every way in which its
+    /// state breaks the rules.
+    fn build(self, pos: PlayerPos, rules: &Rules) -> (Player, Vec<String>) {
+        let mut errors: Vec<String> = Vec::new();
+        let mut player = Player::new(&self.name, self.hero_l, self.hero_r);
+        if self.crown == 0 {
+            errors.push(format!("{:?}: crown must be at least 1", pos));
+        }
+        if let Err(e) = player.set_crown(self.crown) {
+            errors.push(format!("{:?}: {}", pos, e));
+        }
+        if let Err(e) = player.set_bulwark(self.bulwark) {
+            errors.push(format!("{:?}: {}", pos, e));
+        }
+        errors.extend(
+            validate_player(pos, &player, rules).iter().map(|v| v.to_string()));
+        (player, errors)
+    }
+}
+
+/// Sets up a [`Game`] from an arbitrary position.
+///
+/// Everything not set explicitly starts as in a new game. The position is
+/// only checked against the rules when the game is built, so setters may be
+/// called in any order.
+#[derive(Clone, Debug)]
+pub struct GameBuilder {
+    p1: PlayerSpec,
+    p2: PlayerSpec,
+    rules: Rules,
+    turn: usize,
+}
+
+impl GameBuilder {
+    /// Start from a new game between players with the given `(left, right)`
+    /// heroes.
+    pub fn new(
+        heroes_p1: (HeroKind, HeroKind),
+        heroes_p2: (HeroKind, HeroKind),
+    ) -> Self
+    {
+        Self {
+            p1: PlayerSpec::new("Player 1", heroes_p1),
+            p2: PlayerSpec::new("Player 2", heroes_p2),
+            rules: Rules::default(),
+            turn: 0,
+        }
+    }
+
+    fn get_mut(&mut self, player: PlayerPos) -> &mut PlayerSpec {
+        match player {
+            PlayerPos::P1 => &mut self.p1,
+            PlayerPos::P2 => &mut self.p2,
+        }
+    }
+
+    pub fn rules(mut self, rules: Rules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Set the number of turns already played.
+    pub fn turn(mut self, turn: usize) -> Self {
+        self.turn = turn;
+        self
+    }
+
+    pub fn name(mut self, player: PlayerPos, name: &str) -> Self {
+        self.get_mut(player).name = name.to_string();
+        self
+    }
+
+    pub fn crown(mut self, player: PlayerPos, crown: u8) -> Self {
+        self.get_mut(player).crown = crown;
+        self
+    }
+
+    pub fn bulwark(mut self, player: PlayerPos, bulwark: u8) -> Self {
+        self.get_mut(player).bulwark = bulwark;
+        self
+    }
+
+    pub fn level(mut self, player: PlayerPos, hero: HeroPos, level: u8)
+        -> Self
+    {
+        *self.get_mut(player).get_hero_mut(hero).get_level_mut() = level;
+        self
+    }
+
+    pub fn exp(mut self, player: PlayerPos, hero: HeroPos, exp: u8) -> Self {
+        *self.get_mut(player).get_hero_mut(hero).get_exp_mut() = exp;
+        self
+    }
+
+    pub fn energy(mut self, player: PlayerPos, hero: HeroPos, energy: u8)
+        -> Self
+    {
+        *self.get_mut(player).get_hero_mut(hero).get_energy_mut() = energy;
+        self
+    }
+
+    pub fn act(mut self, player: PlayerPos, hero: HeroPos, act: bool) -> Self {
+        self.get_mut(player).get_hero_mut(hero).set_act(act);
+        self
+    }
+
+    /// Check the position against the rules and build the game, or else
+    /// describe every problem found, one per line.
+    pub fn build(self) -> Result<Game, String> {
+        let (p1, mut errors) = self.p1.build(PlayerPos::P1, &self.rules);
+        let (p2, errors_p2) = self.p2.build(PlayerPos::P2, &self.rules);
+        errors.extend(errors_p2);
+        if !errors.is_empty() {
+            return Err(errors.join("\n"));
+        }
+        let mut game = Game::new(p1, p2);
+        game.set_rules(self.rules);
+        game.set_turn(self.turn);
+        Ok(game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn builder() -> GameBuilder {
+        GameBuilder::new(
+            (HeroKind::Warrior, HeroKind::Mage),
+            (HeroKind::Archer, HeroKind::Priest),
+        )
+    }
+
+    #[test]
+    fn rejects_zero_crown() {
+        let err = builder().crown(PlayerPos::P2, 0).build().unwrap_err();
+        assert_eq!(err, "P2: crown must be at least 1");
+    }
+
+    #[test]
+    fn rejects_energy_beyond_rod() {
+        let err
+            = builder()
+            .energy(PlayerPos::P1, HeroPos::R, 6)
+            .build()
+            .unwrap_err();
+        assert_eq!(err, "P1 R Mage: energy 6 exceeds the rod length of 5");
+    }
+
+    #[test]
+    fn rejects_same_heroes() {
+        let err
+            = GameBuilder::new(
+                (HeroKind::Warrior, HeroKind::Warrior),
+                (HeroKind::Archer, HeroKind::Priest),
+            )
+            .build()
+            .unwrap_err();
+        assert_eq!(err, "P1: both heroes are Warrior");
+    }
+
+    #[test]
+    fn collects_every_error() {
+        let err
+            = builder()
+            .crown(PlayerPos::P1, 0)
+            .energy(PlayerPos::P2, HeroPos::L, 5)
+            .build()
+            .unwrap_err();
+        assert_eq!(err.lines().count(), 2);
+    }
+
+    #[test]
+    fn builds_endgame() {
+        let rules = Rules { max_turns: Some(20), ..Rules::default() };
+        let game
+            = builder()
+            .rules(rules)
+            .turn(19)
+            .crown(PlayerPos::P1, 1)
+            .bulwark(PlayerPos::P1, 5)
+            .level(PlayerPos::P1, HeroPos::L, 2)
+            .exp(PlayerPos::P1, HeroPos::L, 3)
+            .energy(PlayerPos::P1, HeroPos::L, 2)
+            .crown(PlayerPos::P2, 2)
+            .level(PlayerPos::P2, HeroPos::R, 1)
+            .build()
+            .unwrap();
+        assert_eq!(game.get_turn(), 19);
+        assert_eq!(*game.get_rules(), rules);
+        let p1 = game.get_player(PlayerPos::P1);
+        assert_eq!((p1.get_crown(), p1.get_bulwark()), (1, 5));
+        let warrior = p1.get_hero(HeroPos::L);
+        assert_eq!(
+            (warrior.get_level(), warrior.get_exp(), warrior.get_energy()),
+            (2, 3, 2),
+        );
+        let p2 = game.get_player(PlayerPos::P2);
+        assert_eq!(p2.get_crown(), 2);
+        assert_eq!(p2.get_hero(HeroPos::R).get_level(), 1);
+    }
+}
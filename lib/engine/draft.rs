@@ -0,0 +1,215 @@
+use rand::Rng;
+use crate::engine::{
+    game::PlayerPos,
+    hero::{ Hero, HeroKind },
+    player::Player,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DraftAction {
+    /// Remove a hero from the draft.
+    Ban,
+    /// Take a hero for one's own team.
+    Pick,
+}
+
+impl DraftAction {
+    pub fn past_tense(&self) -> &'static str {
+        match self {
+            Self::Ban => "banned",
+            Self::Pick => "picked",
+        }
+    }
+}
+
+impl std::fmt::Display for DraftAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ban => "ban".fmt(f),
+            Self::Pick => "pick".fmt(f),
+        }
+    }
+}
+
+/// A single ban or pick made during a draft.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DraftStep {
+    pub player: PlayerPos,
+    pub action: DraftAction,
+    pub hero: HeroKind,
+}
+
+/// Order in which players act during a draft: alternating bans, followed by
+/// picks in snake order.
+pub const DRAFT_ORDER: [(PlayerPos, DraftAction); 6] = [
+    (PlayerPos::P1, DraftAction::Ban),
+    (PlayerPos::P2, DraftAction::Ban),
+    (PlayerPos::P1, DraftAction::Pick),
+    (PlayerPos::P2, DraftAction::Pick),
+    (PlayerPos::P2, DraftAction::Pick),
+    (PlayerPos::P1, DraftAction::Pick),
+];
+
+/// Hero selection by bans and picks, where each hero can be banned or picked
+/// at most once.
+///
+/// Each player's first pick becomes their left hero, and their second pick
+/// their right hero.
+#[derive(Clone, Debug, Default)]
+pub struct Draft {
+    steps: Vec<DraftStep>,
+}
+
+impl Draft {
+    pub fn new() -> Self { Self::default() }
+
+    /// All bans and picks made so far, in order.
+    pub fn get_steps(&self) -> &[DraftStep] { &self.steps }
+
+    /// The player to act next and what they must do, or `None` if the draft
+    /// is over.
+    pub fn next_step(&self) -> Option<(PlayerPos, DraftAction)> {
+        DRAFT_ORDER.get(self.steps.len()).copied()
+    }
+
+    pub fn is_done(&self) -> bool { self.next_step().is_none() }
+
+    /// The ban or pick that removed `hero` from the draft, if any.
+    pub fn taken_by(&self, hero: HeroKind) -> Option<&DraftStep> {
+        self.steps.iter().find(|step| step.hero == hero)
+    }
+
+    /// Heroes that have been neither banned nor picked.
+    pub fn available(&self) -> Vec<HeroKind> {
+        HeroKind::all().into_iter()
+            .filter(|kind| self.taken_by(*kind).is_none())
+            .collect()
+    }
+
+    /// Ban or pick `hero` for the player whose turn it is.
+    pub fn take(&mut self, hero: HeroKind) -> Result<DraftStep, String> {
+        let (player, action)
+            = self.next_step()
+            .ok_or_else(|| "the draft is over".to_string())?;
+        if let Some(step) = self.taken_by(hero) {
+            return Err(format!("{} has already been {}",
+                hero, step.action.past_tense()));
+        }
+        let step = DraftStep { player, action, hero };
+        self.steps.push(step);
+        Ok(step)
+    }
+
+    /// Heroes picked by `player`, in order.
+    pub fn get_picks(&self, player: PlayerPos) -> Vec<HeroKind> {
+        self.steps.iter()
+            .filter(|step| {
+                step.player == player && step.action == DraftAction::Pick
+            })
+            .map(|step| step.hero)
+            .collect()
+    }
+
+    /// Choose a hero to ban or pick at random.
+    pub fn choose_cpu<R>(&self, rng: &mut R) -> HeroKind
+    where R: Rng + ?Sized
+    {
+        let available = self.available();
+        available[rng.gen_range(0..available.len())]
+    }
+
+    /// Build both players from a finished draft.
+    pub fn finish(&self, name_p1: &str, name_p2: &str)
+        -> Result<(Player, Player), String>
+    {
+        let player = |pos: PlayerPos, name: &str| -> Result<Player, String> {
+            match self.get_picks(pos).as_slice() {
+                [l, r] => {
+                    let (l, r) = (Hero::from_kind(*l), Hero::from_kind(*r));
+                    Ok(Player::new(name, l, r))
+                },
+                _ => Err("the draft is not finished".to_string()),
+            }
+        };
+        Ok((player(PlayerPos::P1, name_p1)?, player(PlayerPos::P2, name_p2)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{ SeedableRng, rngs::StdRng };
+    use crate::engine::player::HeroPos;
+    use super::*;
+
+    #[test]
+    fn order() {
+        let mut draft = Draft::new();
+        for (i, kind) in HeroKind::all().into_iter().enumerate() {
+            assert_eq!(draft.next_step(), Some(DRAFT_ORDER[i]));
+            let step = draft.take(kind).unwrap();
+            assert_eq!((step.player, step.action), DRAFT_ORDER[i]);
+        }
+        assert!(draft.is_done());
+        assert_eq!(draft.next_step(), None);
+        assert_eq!(
+            draft.take(HeroKind::Warrior),
+            Err("the draft is over".to_string()),
+        );
+    }
+
+    #[test]
+    fn take_rejects_taken_heroes() {
+        let mut draft = Draft::new();
+        draft.take(HeroKind::Mage).unwrap();
+        draft.take(HeroKind::Archer).unwrap();
+        draft.take(HeroKind::Priest).unwrap();
+        assert_eq!(
+            draft.take(HeroKind::Mage),
+            Err("Mage has already been banned".to_string()),
+        );
+        assert_eq!(
+            draft.take(HeroKind::Priest),
+            Err("Priest has already been picked".to_string()),
+        );
+        // a rejected hero does not use up the turn
+        assert_eq!(draft.get_steps().len(), 3);
+        assert_eq!(draft.next_step(), Some(DRAFT_ORDER[3]));
+    }
+
+    #[test]
+    fn cpu_chooses_available() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut draft = Draft::new();
+        while !draft.is_done() {
+            let available = draft.available();
+            assert_eq!(
+                available.len(),
+                HeroKind::all().len() - draft.get_steps().len(),
+            );
+            let hero = draft.choose_cpu(&mut rng);
+            assert!(available.contains(&hero));
+            draft.take(hero).unwrap();
+        }
+        assert!(draft.available().is_empty());
+    }
+
+    #[test]
+    fn finish() {
+        let mut draft = Draft::new();
+        let [_, _, p1_l, p2_l, p2_r, p1_r] = HeroKind::all();
+        for kind in HeroKind::all() {
+            assert_eq!(
+                draft.finish("Alice", "Bob").unwrap_err(),
+                "the draft is not finished",
+            );
+            draft.take(kind).unwrap();
+        }
+        let (p1, p2) = draft.finish("Alice", "Bob").unwrap();
+        assert_eq!(p1.get_name(), "Alice");
+        assert_eq!(p1.get_hero(HeroPos::L).get_kind(), p1_l);
+        assert_eq!(p1.get_hero(HeroPos::R).get_kind(), p1_r);
+        assert_eq!(p2.get_name(), "Bob");
+        assert_eq!(p2.get_hero(HeroPos::L).get_kind(), p2_l);
+        assert_eq!(p2.get_hero(HeroPos::R).get_kind(), p2_r);
+    }
+}
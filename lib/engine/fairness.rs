@@ -0,0 +1,357 @@
+use rand::Rng;
+use crate::engine::{
+    game::PlayerPos,
+    replay::{ PlayerRecord, Replay },
+    wheel::{ Wheel, Rolls, NUM_FACES },
+};
+
+/// Number of symbols on a panel, e.g. 2 for `DD*`.
+fn symbols(panel: &Wheel) -> u8 {
+    match *panel {
+        Wheel::Square(n, _) | Wheel::Diamond(n, _) | Wheel::Hammer(n) => n,
+    }
+}
+
+/// Every panel landed by a fresh spin in one player's turn: all five wheels on
+/// the first spin, then only those left unlocked on each re-spin.
+fn fresh_draws(record: &PlayerRecord) -> Vec<(usize, Wheel)> {
+    let mut draws: Vec<(usize, Wheel)> = Vec::new();
+    let mut locks = [false; 5];
+    let rolls = record.spins.iter().map(|spin| (&spin.rolls, Some(spin.locks)))
+        .chain(record.rolls.as_ref().map(|rolls| (rolls, None)));
+    for (rolls, next_locks) in rolls {
+        for (k, panel) in rolls.iter().enumerate() {
+            if !locks[k] { draws.push((k, *panel)); }
+        }
+        if let Some(next_locks) = next_locks { locks = next_locks; }
+    }
+    draws
+}
+
+/// Landed panels of a single wheel, against the number of its faces showing
+/// each one.
+#[derive(Clone, Debug)]
+pub struct WheelTally {
+    wheel: usize,
+    /// Each distinct panel with the number of faces showing it.
+    panels: Vec<(Wheel, u8)>,
+    counts: Vec<u64>,
+}
+
+impl WheelTally {
+    pub fn new(wheel: usize) -> Self {
+        let mut panels: Vec<(Wheel, u8)> = Vec::new();
+        for face in 0..NUM_FACES {
+            let panel = Wheel::sides(wheel, face);
+            match panels.iter_mut().find(|(p, _)| *p == panel) {
+                Some((_, faces)) => { *faces += 1; },
+                None => { panels.push((panel, 1)); },
+            }
+        }
+        let counts = vec![0; panels.len()];
+        Self { wheel, panels, counts }
+    }
+
+    pub fn get_wheel(&self) -> usize { self.wheel }
+
+    /// Count one landing of `panel`. Fails if `panel` isn't on this wheel.
+    pub fn add(&mut self, panel: Wheel) -> Result<(), String> {
+        let k
+            = self.panels.iter()
+            .position(|(p, _)| *p == panel)
+            .ok_or_else(|| {
+                format!("panel {} is not on wheel {}", panel, self.wheel + 1)
+            })?;
+        self.counts[k] += 1;
+        Ok(())
+    }
+
+    /// Total number of landings counted.
+    pub fn total(&self) -> u64 { self.counts.iter().sum() }
+
+    /// Each distinct panel, with the number of faces showing it, the number of
+    /// landings counted and the number expected of a fair wheel.
+    pub fn rows(&self) -> impl Iterator<Item = (Wheel, u8, u64, f64)> + '_ {
+        let total = self.total() as f64;
+        self.panels.iter().zip(self.counts.iter())
+            .map(move |((panel, faces), count)| {
+                let expected = total * f64::from(*faces) / f64::from(NUM_FACES);
+                (*panel, *faces, *count, expected)
+            })
+    }
+
+    /// Pearson's chi-squared statistic against a fair wheel.
+    pub fn chi_squared(&self) -> f64 {
+        self.rows()
+            .filter(|(_, _, _, expected)| *expected > 0.0)
+            .map(|(_, _, count, expected)| {
+                (count as f64 - expected).powi(2) / expected
+            })
+            .sum()
+    }
+
+    /// Degrees of freedom of [`Self::chi_squared`].
+    pub fn dof(&self) -> usize { self.panels.len() - 1 }
+
+    /// Probability of a fair wheel giving a chi-squared statistic at least as
+    /// large as [`Self::chi_squared`].
+    pub fn p_value(&self) -> f64 {
+        if self.total() == 0 { return 1.0; }
+        chi_squared_sf(self.chi_squared(), self.dof())
+    }
+}
+
+/// Landed panels of all five wheels.
+#[derive(Clone, Debug)]
+pub struct Fairness {
+    tallies: [WheelTally; 5],
+}
+
+impl Default for Fairness {
+    fn default() -> Self { Self::new() }
+}
+
+impl Fairness {
+    pub fn new() -> Self {
+        Self { tallies: std::array::from_fn(WheelTally::new) }
+    }
+
+    pub fn get_tallies(&self) -> &[WheelTally; 5] { &self.tallies }
+
+    /// Count a fresh spin of all five wheels.
+    pub fn add_rolls(&mut self, rolls: &Rolls) -> Result<(), String> {
+        self.tallies.iter_mut().zip(rolls.iter())
+            .try_for_each(|(tally, panel)| tally.add(*panel))
+    }
+
+    /// Count `n` spins generated by [`Wheel::gen_rolls`].
+    pub fn generate<R>(&mut self, n: usize, rng: &mut R)
+    where R: Rng + ?Sized
+    {
+        for _ in 0..n {
+            self.add_rolls(&Wheel::gen_rolls(rng))
+                .expect("generated panels should be on their wheels");
+        }
+    }
+
+    /// Count every panel landed by a fresh spin in a replay. Panels kept by
+    /// a lock are not counted again.
+    pub fn add_replay(&mut self, replay: &Replay) -> Result<(), String> {
+        for turn in replay.turns.iter() {
+            for pos in [PlayerPos::P1, PlayerPos::P2] {
+                for (k, panel) in fresh_draws(turn.get(pos)) {
+                    self.tallies[k].add(panel)
+                        .map_err(|e| format!("turn {}: {}", turn.turn, e))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Observed against expected frequencies of each panel, with a chi-squared
+/// test per wheel.
+impl std::fmt::Display for Fairness {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for tally in self.tallies.iter() {
+            writeln!(f, "Wheel {} ({} spins)",
+                tally.get_wheel() + 1, tally.total())?;
+            writeln!(f, "  {:<6} {:>5} {:>10} {:>12} {:>8} {:>8}",
+                "panel", "faces", "observed", "expected", "obs %", "exp %")?;
+            let total = tally.total().max(1) as f64;
+            for (panel, faces, count, expected) in tally.rows() {
+                writeln!(f, "  {:<6} {:>5} {:>10} {:>12.1} {:>8.3} {:>8.3}",
+                    panel.to_string(), faces, count, expected,
+                    100.0 * count as f64 / total,
+                    100.0 * f64::from(faces) / f64::from(NUM_FACES))?;
+            }
+            writeln!(f, "  chi-squared = {:.3} ({} dof), p = {:.4}",
+                tally.chi_squared(), tally.dof(), tally.p_value())?;
+        }
+        Ok(())
+    }
+}
+
+/// How far the panels a player landed by fresh spins were from what fair
+/// wheels give on average, by their number of symbols.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Luck {
+    /// Number of panels landed by fresh spins.
+    pub draws: usize,
+    /// Total number of symbols on those panels.
+    pub symbols: u64,
+    /// Expected value of `symbols`.
+    pub expected: f64,
+    /// Variance of `symbols`.
+    pub variance: f64,
+}
+
+impl Luck {
+    /// Luck of the player at `pos` over the turns of `replay`.
+    pub fn of(replay: &Replay, pos: PlayerPos) -> Self {
+        // mean and variance of the number of symbols on each wheel
+        let moments: Vec<(f64, f64)>
+            = (0..5)
+            .map(|wheel| {
+                let n: Vec<f64>
+                    = (0..NUM_FACES)
+                    .map(|face| f64::from(symbols(&Wheel::sides(wheel, face))))
+                    .collect();
+                let mean = n.iter().sum::<f64>() / n.len() as f64;
+                let var
+                    = n.iter().map(|x| (x - mean).powi(2)).sum::<f64>()
+                    / n.len() as f64;
+                (mean, var)
+            })
+            .collect();
+        let mut luck = Self::default();
+        for turn in replay.turns.iter() {
+            for (k, panel) in fresh_draws(turn.get(pos)) {
+                luck.draws += 1;
+                luck.symbols += u64::from(symbols(&panel));
+                luck.expected += moments[k].0;
+                luck.variance += moments[k].1;
+            }
+        }
+        luck
+    }
+
+    /// Number of standard deviations by which the player landed more symbols
+    /// than expected; positive is lucky and negative unlucky.
+    pub fn score(&self) -> f64 {
+        if self.variance <= 0.0 { return 0.0; }
+        (self.symbols as f64 - self.expected) / self.variance.sqrt()
+    }
+}
+
+impl std::fmt::Display for Luck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:+.2} sd ({} symbols on {} spun panels, {:.1} expected)",
+            self.score(), self.symbols, self.draws, self.expected)
+    }
+}
+
+/// Survival function of the chi-squared distribution with `dof` degrees of
+/// freedom.
+fn chi_squared_sf(x: f64, dof: usize) -> f64 {
+    if dof == 0 { return 1.0; }
+    gamma_q(dof as f64 / 2.0, x / 2.0)
+}
+
+/// Natural log of the gamma function, by the Lanczos approximation.
+fn ln_gamma(x: f64) -> f64 {
+    const COEFS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let sum: f64
+        = COEFS.iter().enumerate().skip(1)
+        .map(|(k, c)| c / (x + k as f64))
+        .sum::<f64>()
+        + COEFS[0];
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t
+        + sum.ln()
+}
+
+/// Regularized upper incomplete gamma function Q(a, x), by its series for
+/// small `x` and its continued fraction otherwise.
+fn gamma_q(a: f64, x: f64) -> f64 {
+    const EPS: f64 = 1e-14;
+    const MAX_ITERS: usize = 1000;
+    if x <= 0.0 { return 1.0; }
+    let prefactor = (a * x.ln() - x - ln_gamma(a)).exp();
+    if x < a + 1.0 {
+        let mut term = 1.0 / a;
+        let mut sum = term;
+        for n in 1..MAX_ITERS {
+            term *= x / (a + n as f64);
+            sum += term;
+            if term.abs() < sum.abs() * EPS { break; }
+        }
+        1.0 - sum * prefactor
+    } else {
+        // modified Lentz's method
+        let tiny = f64::MIN_POSITIVE / EPS;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for n in 1..MAX_ITERS {
+            let an = -(n as f64) * (n as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < tiny { d = tiny; }
+            c = b + an / c;
+            if c.abs() < tiny { c = tiny; }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < EPS { break; }
+        }
+        prefactor * h
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, tol: f64) {
+        assert!((actual - expected).abs() < tol,
+            "expected {}, got {}", expected, actual);
+    }
+
+    #[test]
+    fn ln_gamma_values() {
+        assert_close(ln_gamma(1.0), 0.0, 1e-12);
+        assert_close(ln_gamma(5.0), 24_f64.ln(), 1e-12);
+        assert_close(ln_gamma(0.5), std::f64::consts::PI.sqrt().ln(), 1e-12);
+        // 10! = 3628800
+        assert_close(ln_gamma(11.0), 3_628_800_f64.ln(), 1e-10);
+    }
+
+    #[test]
+    fn gamma_q_values() {
+        // Q(1, x) = e^-x and Q(2, x) = e^-x (1 + x)
+        assert_close(gamma_q(1.0, 0.5), (-0.5_f64).exp(), 1e-12);
+        assert_close(gamma_q(2.0, 2.603), 0.267, 1e-3);
+        assert_close(gamma_q(2.0, 2.603), (-2.603_f64).exp() * 3.603, 1e-12);
+        assert_close(gamma_q(2.0, 10.0), (-10_f64).exp() * 11.0, 1e-14);
+        assert_eq!(gamma_q(3.0, 0.0), 1.0);
+    }
+
+    #[test]
+    fn gamma_q_is_continuous_at_switch() {
+        // Q(3, x) = e^-x (1 + x + x^2 / 2), switching methods at x = 4
+        let exact = |x: f64| (-x).exp() * (1.0 + x + x * x / 2.0);
+        for x in [4.0 - 1e-9, 4.0, 4.0 + 1e-9] {
+            assert_close(gamma_q(3.0, x), exact(x), 1e-12);
+        }
+        for a in [0.5, 1.5, 7.5, 30.0] {
+            let below = gamma_q(a, a + 1.0 - 1e-9);
+            let above = gamma_q(a, a + 1.0 + 1e-9);
+            assert_close(below, above, 1e-8);
+        }
+    }
+
+    #[test]
+    fn chi_squared_values() {
+        assert_close(chi_squared_sf(3.841, 1), 0.05, 1e-4);
+        assert_close(chi_squared_sf(5.991, 2), 0.05, 1e-4);
+        assert_close(chi_squared_sf(3.0, 2), (-1.5_f64).exp(), 1e-12);
+        assert_eq!(chi_squared_sf(3.0, 0), 1.0);
+    }
+}
@@ -1,23 +1,49 @@
+use std::cmp::Ordering;
+#[cfg(feature = "terminal")]
 use std::{
     thread,
     time,
     io,
 };
-use rand::{ prelude::thread_rng };
+use rand::Rng;
+#[cfg(feature = "terminal")]
+use rand::prelude::thread_rng;
+#[cfg(feature = "terminal")]
 use crate::{
     print_flush,
+    engine::{
+        draft::{ Draft, DraftAction },
+        locks,
+    },
+};
+use crate::{
     println_flush,
     engine::{
         hero::{ Hero, HeroKind, MAX_LEVEL, MAX_EXP },
         player::{ Player, HeroPos },
-        wheel::{ Wheel, WheelKind, Rolls },
+        wheel::{ Wheel, Rolls },
+        advisor,
+        strategy::Strategy,
+        replay::{ Replay, ReplayPlayer, TurnRecord, PlayerRecord, Spin },
+        rules::Rules,
+        draft::DraftStep,
+        validate::{ Violation, validate_player },
+        handicap::Handicap,
+        stats::MatchStats,
+        meter::WinMeter,
     },
 };
 
 pub const DISPW: usize = 80;
 pub const TEXTW: usize = DISPW / 2 - 4;
 
-#[derive(Copy, Clone, Debug)]
+/// Number of turns after which a headless game is called a draw.
+pub const HEADLESS_TURN_LIMIT: usize = 500;
+
+/// Crown damage dealt by a bomb, before any sudden-death escalation.
+pub const BOMB_DMG: u8 = 2;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum PlayerPos {
     P1,
     P2,
@@ -47,94 +73,110 @@ impl std::fmt::Display for PlayerId {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum LevelUpKind {
     Up(u8),
     Max,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Damage {
     Crown(u8),
     Bulwark(u8),
 }
 
-#[derive(Debug)]
+/// Something that happened while resolving a turn, by the player whose
+/// heroes or panels caused it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Action {
-    ExpUp(PlayerId, HeroKind, u8),
-    LevelUp(PlayerId, HeroKind, LevelUpKind),
-    BulwarkUp(PlayerId, u8),
-    EnergyAdd(PlayerId, HeroKind, u8),
-    AssassinDamage(PlayerId, u8),
-    AssassinDelay(PlayerId, PlayerId, HeroKind, u8),
-    PriestHeal(PlayerId, u8),
-    PriestEnergy(PlayerId, HeroKind, u8),
-    EngineerBuild(PlayerId, u8),
-    HeroDamage(PlayerId, HeroKind, Damage),
-    Bomb(PlayerId, u8),
+    ExpUp(PlayerPos, HeroKind, u8),
+    LevelUp(PlayerPos, HeroKind, LevelUpKind),
+    BulwarkUp(PlayerPos, u8),
+    EnergyAdd(PlayerPos, HeroKind, u8),
+    ExpCarry(PlayerPos, HeroKind, u8),
+    EnergyCarry(PlayerPos, HeroKind, u8),
+    AssassinDamage(PlayerPos, u8),
+    AssassinDelay(PlayerPos, HeroKind, u8),
+    PriestHeal(PlayerPos, u8),
+    PriestEnergy(PlayerPos, HeroKind, u8),
+    EngineerBuild(PlayerPos, u8),
+    HeroDamage(PlayerPos, HeroKind, Damage),
+    Bomb(PlayerPos, u8),
 }
 
 impl Action {
-    pub fn msg(&self) -> String {
-        match self {
+    /// Describe the action, naming players as in `game`.
+    pub fn msg(&self, game: &Game) -> String {
+        let id = |pos: PlayerPos| game.get_player_id(pos);
+        match *self {
             Self::ExpUp(player, hero, exp) => {
-                format!("{}'s {:?} gains {} EXP", player, hero, exp)
+                format!("{}'s {:?} gains {} EXP", id(player), hero, exp)
             },
             Self::LevelUp(player, hero, level_up) => {
                 match level_up {
                     LevelUpKind::Up(lvl) => {
-                        format!("{}'s {:?} levels up to {}", player, hero, lvl)
+                        format!("{}'s {:?} levels up to {}",
+                            id(player), hero, lvl)
                     },
                     LevelUpKind::Max => {
-                        format!(
-                            "{}'s {:?} is already at max level!", player, hero)
+                        format!("{}'s {:?} is already at max level!",
+                            id(player), hero)
                     },
                 }
             },
             Self::BulwarkUp(player, inc) => {
-                format!("{}'s bulwark increases by {}", player, inc)
+                format!("{}'s bulwark increases by {}", id(player), inc)
             },
             Self::EnergyAdd(player, hero, energy) => {
-                format!("{}'s {:?} gains {} energy", player, hero, energy)
+                format!("{}'s {:?} gains {} energy", id(player), hero, energy)
+            },
+            Self::ExpCarry(player, hero, exp) => {
+                format!("{}'s {:?} carries {} EXP over to the next level",
+                    id(player), hero, exp)
+            },
+            Self::EnergyCarry(player, hero, energy) => {
+                format!("{}'s {:?} carries {} energy over to the next rod",
+                    id(player), hero, energy)
             },
             Self::AssassinDamage(player, damage) => {
-                format!(
-                    "{}'s Assassin deals {} damage to Crown", player, damage)
+                format!("{}'s Assassin deals {} damage to Crown",
+                    id(player), damage)
             },
-            Self::AssassinDelay(player, opponent, hero, delay) => {
+            Self::AssassinDelay(player, hero, delay) => {
                 format!(
                     "{}'s Assassin removes {} energy from {}'s {:?}",
-                    player, delay, opponent, hero,
+                    id(player), delay, id(player.other()), hero,
                 )
             },
             Self::PriestHeal(player, heal) => {
-                format!("{}'s Priest heals Crown by {}", player, heal)
+                format!("{}'s Priest heals Crown by {}", id(player), heal)
             },
             Self::PriestEnergy(player, hero, energy) => {
-                format!(
-                    "{}'s Priest gives {} energy to {:?}", player, energy, hero)
+                format!("{}'s Priest gives {} energy to {:?}",
+                    id(player), energy, hero)
             },
             Self::EngineerBuild(player, build) => {
-                format!("{}'s Engineer adds {} to Bulwark", player, build)
+                format!("{}'s Engineer adds {} to Bulwark", id(player), build)
             },
             Self::HeroDamage(player, hero, damage) => {
                 match damage {
                     Damage::Crown(dmg) => {
                         format!(
                             "{}'s {:?} deals {} damage to Crown",
-                            player, hero, dmg,
+                            id(player), hero, dmg,
                         )
                     },
                     Damage::Bulwark(dmg) => {
                         format!(
                             "{}'s {:?} deals {} damage to Bulwark",
-                            player, hero, dmg,
+                            id(player), hero, dmg,
                         )
                     },
                 }
             },
             Self::Bomb(player, damage) => {
-                format!("{}'s Bomb does {} damage to Crown", player, damage)
+                format!("{}'s Bomb does {} damage to Crown",
+                    id(player), damage)
             },
         }
     }
@@ -142,6 +184,24 @@ impl Action {
 
 pub type TurnLog = Vec<Action>;
 
+/// A destination for the [`Action`]s taken while resolving a turn.
+pub trait ActionLog {
+    fn push(&mut self, action: Action);
+}
+
+impl ActionLog for TurnLog {
+    fn push(&mut self, action: Action) { Vec::push(self, action); }
+}
+
+/// An [`ActionLog`] that discards everything, for resolving turns as fast as
+/// possible.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct NoLog;
+
+impl ActionLog for NoLog {
+    fn push(&mut self, _action: Action) { }
+}
+
 #[derive(Copy, Clone, Debug)]
 pub enum Bomb { Yes, No }
 
@@ -346,20 +406,85 @@ impl Acts {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+/// How a game that reached its turn limit was decided.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TieBreak {
+    /// One player had the higher Crown.
+    Crown,
+    /// Crowns were equal, but one player had the higher Bulwark.
+    Bulwark,
+    /// Crowns and Bulwarks were both equal.
+    Tied,
+}
+
+/// Why a game ended.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EndReason {
+    /// At least one Crown fell to 0.
+    Crown,
+    /// The turn limit set by the rules was reached.
+    TurnLimit(TieBreak),
+    /// The game was called off after [`HEADLESS_TURN_LIMIT`] turns.
+    Abandoned,
+}
+
+impl std::fmt::Display for EndReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Crown => "a Crown fell to 0".fmt(f),
+            Self::TurnLimit(TieBreak::Crown) => {
+                "turn limit reached, decided by Crown".fmt(f)
+            },
+            Self::TurnLimit(TieBreak::Bulwark) => {
+                "turn limit reached, decided by Bulwark".fmt(f)
+            },
+            Self::TurnLimit(TieBreak::Tied) => {
+                "turn limit reached with Crown and Bulwark tied".fmt(f)
+            },
+            Self::Abandoned => "called off after too many turns".fmt(f),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Winner {
-    P1,
-    P2,
-    Draw,
+    P1(EndReason),
+    P2(EndReason),
+    Draw(EndReason),
+}
+
+impl Winner {
+    /// The winning player, if not a draw.
+    pub fn player(&self) -> Option<PlayerPos> {
+        match self {
+            Self::P1(_) => Some(PlayerPos::P1),
+            Self::P2(_) => Some(PlayerPos::P2),
+            Self::Draw(_) => None,
+        }
+    }
+
+    pub fn reason(&self) -> EndReason {
+        match *self {
+            Self::P1(reason) | Self::P2(reason) | Self::Draw(reason) => reason,
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct Game {
     p1: Player,
     p2: Player,
+    rules: Rules,
+    /// Number of turns resolved so far.
+    turn: usize,
+    draft: Vec<DraftStep>,
+    replay: Option<Replay>,
+    stats: Option<MatchStats>,
+    meter: Option<WinMeter>,
 }
 
 impl Game {
+    #[cfg(feature = "terminal")]
     pub fn get_choose() -> Self {
         let mut _buf = String::new();
         print_flush!("Press ENTER to start: ");
@@ -371,6 +496,7 @@ impl Game {
         Self::new(p1, p2)
     }
 
+    #[cfg(feature = "terminal")]
     pub fn get_choose_singleplayer() -> Self {
         let mut _buf = String::new();
         print_flush!("Press ENTER to start: ");
@@ -380,9 +506,258 @@ impl Game {
         Self::new(p1, p2)
     }
 
-    pub fn new(p1: Player, p2: Player) -> Self { Self { p1, p2 } }
+    pub fn new(p1: Player, p2: Player) -> Self {
+        Self {
+            p1,
+            p2,
+            rules: Rules::default(),
+            turn: 0,
+            draft: Vec::new(),
+            replay: None,
+            stats: None,
+            meter: None,
+        }
+    }
+
+    /// Choose names, then choose heroes by a [`Draft`]. If `p2_cpu` is `true`,
+    /// Player 2's bans and picks are made by the computer.
+    #[cfg(feature = "terminal")]
+    pub fn get_choose_draft(p2_cpu: bool) -> Self {
+        let mut _buf = String::new();
+        print_flush!("Press ENTER to start: ");
+        io::stdin().read_line(&mut _buf).expect("error reading ENTER");
+        println_flush!("Player 1:");
+        let name_p1 = Player::get_choose_name();
+        let name_p2
+            = if p2_cpu {
+                "CPU".to_string()
+            } else {
+                println_flush!("Player 2:");
+                Player::get_choose_name()
+            };
+        Self::get_choose_draft_named(&name_p1, &name_p2, p2_cpu)
+    }
+
+    /// Like [`Self::get_choose_draft`], but for players who have already
+    /// chosen names.
+    #[cfg(feature = "terminal")]
+    pub fn get_choose_draft_named(name_p1: &str, name_p2: &str, p2_cpu: bool)
+        -> Self
+    {
+        let mut draft = Draft::new();
+        let mut rng = thread_rng();
+        while let Some((player, action)) = draft.next_step() {
+            let name
+                = match player {
+                    PlayerPos::P1 => name_p1,
+                    PlayerPos::P2 => name_p2,
+                };
+            let hero
+                = if p2_cpu && player == PlayerPos::P2 {
+                    sleep(500);
+                    draft.choose_cpu(&mut rng)
+                } else {
+                    Self::get_draft_choice(&draft, name, action)
+                };
+            draft.take(hero).expect("hero should be available");
+            println_flush!("{} {}s {}", name, action, hero);
+        }
+        let (p1, p2)
+            = draft.finish(name_p1, name_p2)
+            .expect("draft should be finished");
+        let mut game = Self::new(p1, p2);
+        game.draft = draft.get_steps().to_vec();
+        game
+    }
+
+    #[cfg(feature = "terminal")]
+    fn get_draft_choice(draft: &Draft, name: &str, action: DraftAction)
+        -> HeroKind
+    {
+        println_flush!("{}, choose a hero to {}:", name, action);
+        for (k, kind) in HeroKind::all().into_iter().enumerate() {
+            match draft.taken_by(kind) {
+                Some(step) => {
+                    println_flush!("[{}] {} ({} by {:?})",
+                        k + 1, kind, step.action.past_tense(), step.player);
+                },
+                None => { println_flush!("[{}] {}", k + 1, kind); },
+            }
+        }
+        loop {
+            let kind = Hero::get_choose().get_kind();
+            if draft.taken_by(kind).is_none() {
+                break kind;
+            }
+            println_flush!("{} is not available", kind);
+        }
+    }
+
+    /// Swap the seats of both players, so that Player 1 becomes Player 2 and
+    /// vice versa.
+    pub fn swap_players(&mut self) {
+        std::mem::swap(&mut self.p1, &mut self.p2);
+        self.draft.iter_mut()
+            .for_each(|step| { step.player = step.player.other(); });
+    }
+
+    /// The bans and picks by which heroes were chosen, if they were drafted.
+    pub fn get_draft(&self) -> &[DraftStep] { &self.draft }
+
+    pub fn get_rules(&self) -> &Rules { &self.rules }
+
+    pub fn set_rules(&mut self, rules: Rules) { self.rules = rules; }
+
+    /// Number of turns resolved so far.
+    pub fn get_turn(&self) -> usize { self.turn }
+
+    pub fn set_turn(&mut self, turn: usize) { self.turn = turn; }
+
+    /// Whether `player`'s rolls resolve on turn number `turn` under the
+    /// current rules.
+    fn is_active(&self, player: PlayerPos, turn: usize) -> bool {
+        self.rules.turn_mode.is_active(player, turn)
+    }
+
+    /// Start recording a [`Replay`] of every turn played from here on.
+    pub fn start_replay(&mut self, p1_cpu: bool, p2_cpu: bool) {
+        let name_p1 = self.p1.get_name().to_string();
+        let name_p2 = self.p2.get_name().to_string();
+        let mut replay
+            = Replay::new(
+                ReplayPlayer { name: name_p1, cpu: p1_cpu },
+                ReplayPlayer { name: name_p2, cpu: p2_cpu },
+            );
+        replay.rules = self.rules;
+        replay.draft = self.draft.clone();
+        self.replay = Some(replay);
+    }
+
+    pub fn get_replay(&self) -> Option<&Replay> { self.replay.as_ref() }
+
+    /// Statistics for the game played by [`Self::run`] or
+    /// [`Self::run_vs_cpu`], if any.
+    pub fn get_stats(&self) -> Option<&MatchStats> { self.stats.as_ref() }
+
+    /// Show each player's estimated chance to win on the board in
+    /// [`Self::run`] and [`Self::run_vs_cpu`], along with how much the last
+    /// turn changed it. Off by default.
+    pub fn set_win_meter(&mut self, meter: Option<WinMeter>) {
+        self.meter = meter;
+    }
+
+    pub fn get_win_meter(&self) -> Option<&WinMeter> { self.meter.as_ref() }
+
+    #[cfg(feature = "terminal")]
+    fn update_win_meter(&mut self) {
+        if let Some(mut meter) = self.meter.take() {
+            meter.update(self);
+            self.meter = Some(meter);
+        }
+    }
+
+    fn record_stats(
+        &mut self,
+        rolls_p1: Option<&Rolls>,
+        rolls_p2: Option<&Rolls>,
+        log: &TurnLog,
+    ) {
+        if let Some(mut stats) = self.stats.take() {
+            stats.record_turn(self, rolls_p1, rolls_p2, log);
+            self.stats = Some(stats);
+        }
+    }
+
+    fn record_turn(&mut self, turn: usize) {
+        if let Some(replay) = self.replay.as_mut() {
+            replay.turns.push(TurnRecord {
+                turn,
+                p1: PlayerRecord::new(self.p1.clone()),
+                p2: PlayerRecord::new(self.p2.clone()),
+            });
+        }
+    }
+
+    fn record_spin(
+        &mut self,
+        player: PlayerPos,
+        rolls: &Rolls,
+        locks: &[bool; 5],
+        spins_left: usize,
+    ) {
+        let turn = self.replay.as_mut().and_then(|r| r.turns.last_mut());
+        if let Some(turn) = turn {
+            let spin = Spin { rolls: *rolls, locks: *locks, spins_left };
+            turn.get_mut(player).spins.push(spin);
+        }
+    }
+
+    fn record_rolls(&mut self, player: PlayerPos, rolls: &Rolls) {
+        let turn = self.replay.as_mut().and_then(|r| r.turns.last_mut());
+        if let Some(turn) = turn {
+            turn.get_mut(player).rolls = Some(*rolls);
+        }
+    }
+
+    fn record_winner(&mut self, winner: Winner) {
+        if let Some(replay) = self.replay.as_mut() {
+            replay.winner = Some(winner);
+        }
+    }
+
+    /// Check every invariant of the game state, returning all that are
+    /// broken.
+    pub fn validate(&self) -> Result<(), Vec<Violation>> {
+        let violations: Vec<Violation>
+            = validate_player(PlayerPos::P1, &self.p1, &self.rules)
+            .into_iter()
+            .chain(validate_player(PlayerPos::P2, &self.p2, &self.rules))
+            .collect();
+        if violations.is_empty() { Ok(()) } else { Err(violations) }
+    }
+
+    /// In debug builds, panic if any invariant is broken after `phase` of a
+    /// turn.
+    fn check_phase(&self, phase: &str) {
+        if !cfg!(debug_assertions) { return; }
+        if let Err(violations) = self.validate() {
+            let list: Vec<String>
+                = violations.iter().map(|v| v.to_string()).collect();
+            panic!("invalid state after phase '{}':\n{}",
+                phase, list.join("\n"));
+        }
+    }
 
-    fn get_player(&self, pos: PlayerPos) -> &Player {
+    /// Start `player` over from the position given by `handicap`, keeping
+    /// their name and heroes.
+    pub fn set_handicap(&mut self, player: PlayerPos, handicap: Handicap)
+        -> Result<(), String>
+    {
+        let plr = self.get_player(player);
+        let hero_l = Hero::from_kind(plr.get_hero(HeroPos::L).get_kind());
+        let hero_r = Hero::from_kind(plr.get_hero(HeroPos::R).get_kind());
+        *self.get_player_mut(player)
+            = Player::new_handicap(plr.get_name(), hero_l, hero_r, handicap)?;
+        Ok(())
+    }
+
+    /// Choose a handicap for `player` from the menu.
+    #[cfg(feature = "terminal")]
+    pub fn get_choose_handicap(&mut self, player: PlayerPos) {
+        println_flush!("{}:", self.get_player(player).get_name());
+        loop {
+            let handicap = Handicap::get_choose();
+            match self.set_handicap(player, handicap) {
+                Ok(()) => { break; },
+                Err(e) => {
+                    println_flush!("{}", e);
+                    continue;
+                },
+            }
+        }
+    }
+
+    pub fn get_player(&self, pos: PlayerPos) -> &Player {
         match pos {
             PlayerPos::P1 => &self.p1,
             PlayerPos::P2 => &self.p2,
@@ -462,23 +837,32 @@ impl Game {
         player: PlayerPos,
         hero: HeroPos,
         inc: u8,
-        log: &mut TurnLog,
+        log: &mut dyn ActionLog,
     ) -> Bomb
     {
-        let id = self.get_player_id(player);
+        let carry_over = self.rules.carry_over;
         let hero: &mut Hero = self.get_hero_mut(player, hero);
         let hero_kind = hero.get_kind();
         if inc > 0 {
-            log.push(Action::ExpUp(id.clone(), hero_kind, inc));
-            if hero.exp_inc(inc) {
-                if hero.level_inc() {
-                    log.push(Action::LevelUp(id, hero_kind, LevelUpKind::Max));
-                    Bomb::Yes
-                } else {
-                    log.push(Action::LevelUp(
-                        id, hero_kind, LevelUpKind::Up(hero.get_level())));
-                    Bomb::No
+            log.push(Action::ExpUp(player, hero_kind, inc));
+            if let Some(carry) = exp_inc(hero, inc, carry_over) {
+                let bomb
+                    = if hero.level_inc() {
+                        log.push(Action::LevelUp(
+                            player, hero_kind, LevelUpKind::Max));
+                        Bomb::Yes
+                    } else {
+                        log.push(Action::LevelUp(
+                            player,
+                            hero_kind,
+                            LevelUpKind::Up(hero.get_level()),
+                        ));
+                        Bomb::No
+                    };
+                if carry > 0 {
+                    log.push(Action::ExpCarry(player, hero_kind, carry));
                 }
+                bomb
             } else {
                 Bomb::No
             }
@@ -492,24 +876,35 @@ impl Game {
         player: PlayerPos,
         kind: HeroKind,
         inc: u8,
-        log: &mut TurnLog,
+        log: &mut dyn ActionLog,
     ) -> Option<Bomb>
     {
-        let id = self.get_player_id(player);
+        let carry_over = self.rules.carry_over;
         self.get_hero_of_mut(player, kind)
             .map(|(hero, _)| {
-                if inc > 0 && hero.exp_inc(inc) {
-                    if hero.level_inc() {
-                        log.push(Action::LevelUp(id, kind, LevelUpKind::Max));
+                let Some(carry)
+                    = (inc > 0).then(|| exp_inc(hero, inc, carry_over))
+                    .flatten()
+                else {
+                    return Bomb::No;
+                };
+                let bomb
+                    = if hero.level_inc() {
+                        log.push(Action::LevelUp(
+                            player, kind, LevelUpKind::Max));
                         Bomb::Yes
                     } else {
                         log.push(Action::LevelUp(
-                            id, kind, LevelUpKind::Up(hero.get_level())));
+                            player,
+                            kind,
+                            LevelUpKind::Up(hero.get_level()),
+                        ));
                         Bomb::No
-                    }
-                } else {
-                    Bomb::No
+                    };
+                if carry > 0 {
+                    log.push(Action::ExpCarry(player, kind, carry));
                 }
+                bomb
             })
     }
 
@@ -517,13 +912,12 @@ impl Game {
         &mut self,
         player: PlayerPos,
         points: u8,
-        log: &mut TurnLog,
+        log: &mut dyn ActionLog,
     ) {
-        let id = self.get_player_id(player);
-        let player: &mut Player = self.get_player_mut(player);
+        let plr: &mut Player = self.get_player_mut(player);
         if points > 2 {
-            log.push(Action::BulwarkUp(id, points - 2));
-            player.bulwark_inc(points - 2);
+            log.push(Action::BulwarkUp(player, points - 2));
+            plr.bulwark_inc(points - 2);
         }
     }
 
@@ -532,32 +926,28 @@ impl Game {
         player: PlayerPos,
         hero: HeroPos,
         points: u8,
-        log: &mut TurnLog,
+        log: &mut dyn ActionLog,
     ) -> Act
     {
-        let id = self.get_player_id(player);
+        let carry_over = self.rules.carry_over;
         let hero: &mut Hero = self.get_hero_mut(player, hero);
         let hero_kind = hero.get_kind();
         if points > 2 {
-            log.push(Action::EnergyAdd(id, hero_kind, points - 2));
-            if hero.energy_inc(points - 2) {
-                Act::Yes
-            } else {
-                Act::No
-            }
+            log.push(Action::EnergyAdd(player, hero_kind, points - 2));
+            energy_inc(hero, points - 2, carry_over, player, log)
         } else {
             Act::No
         }
     }
 
-    fn do_assassin(
+    fn do_assassin<R>(
         &mut self,
         player: PlayerPos,
-        log: &mut TurnLog,
+        log: &mut dyn ActionLog,
+        rng: &mut R,
     ) -> Option<(PlayerPos, HeroPos, Bomb)>
+    where R: Rng + ?Sized
     {
-        let id = self.get_player_id(player);
-        let id_opp = self.get_player_id(player.other());
         self.get_hero_of_act_mut(player, HeroKind::Assassin)
             .map(|(hero, pos)| {
                 hero.set_act(false);
@@ -568,13 +958,12 @@ impl Game {
             .map(|(dmg, delay, pos)| {
                 let opp = self.get_player_mut(player.other());
                 // do crown damage
-                log.push(Action::AssassinDamage(id.clone(), dmg));
+                log.push(Action::AssassinDamage(player, dmg));
                 opp.crown_dec(dmg);
                 // do hero delay
-                let (target, _) = opp.get_assassin_target_mut();
+                let (target, _) = opp.get_assassin_target_mut(rng);
                 let target_kind = target.get_kind();
-                log.push(Action::AssassinDelay(
-                    id.clone(), id_opp, target_kind, delay));
+                log.push(Action::AssassinDelay(player, target_kind, delay));
                 target.energy_dec(delay);
                 // +2 EXP from acting
                 (player, pos, self.do_exp_level(player, pos, 2, log))
@@ -584,10 +973,9 @@ impl Game {
     fn do_priest(
         &mut self,
         player: PlayerPos,
-        log: &mut TurnLog,
+        log: &mut dyn ActionLog,
     ) -> Option<(PlayerPos, HeroPos, Bomb)>
     {
-        let id = self.get_player_id(player);
         self.get_hero_of_act_mut(player, HeroKind::Priest)
             .map(|(hero, pos)| {
                 hero.set_act(false);
@@ -601,17 +989,18 @@ impl Game {
                 (heal, egen, pos, t_pos, t_act)
             })
             .map(|(heal, egen, pos, t_pos, t_act)| {
+                let carry_over = self.rules.carry_over;
                 let plr = self.get_player_mut(player);
                 // do crown heal
-                log.push(Action::PriestHeal(id.clone(), heal));
+                log.push(Action::PriestHeal(player, heal));
                 plr.crown_inc(heal);
                 // do energy gen
                 if !t_act {
                     let target = plr.get_hero_mut(t_pos);
                     let target_kind = target.get_kind();
                     log.push(Action::PriestEnergy(
-                        id.clone(), target_kind, egen));
-                    target.energy_inc(egen);
+                        player, target_kind, egen));
+                    energy_inc(target, egen, carry_over, player, log);
                 }
                 // +2 EXP from acting
                 (player, pos, self.do_exp_level(player, pos, 2, log))
@@ -622,9 +1011,8 @@ impl Game {
         &mut self,
         player: PlayerPos,
         prev_acts: Acts,
-        log: &mut TurnLog,
+        log: &mut dyn ActionLog,
     ) {
-        let id = self.get_player_id(player);
         self.get_pos_of(player, HeroKind::Priest)
             .and_then(|pos| {
                 if prev_acts.get(player, pos) == Act::Yes {
@@ -638,11 +1026,12 @@ impl Game {
             .and_then(|(egen, pos)| {
                 let t_pos = pos.other();
                 if prev_acts.get(player, t_pos) == Act::Yes {
+                    let carry_over = self.rules.carry_over;
                     let target = self.get_hero_mut(player, t_pos);
                     let target_kind = target.get_kind();
                     log.push(Action::PriestEnergy(
-                        id.clone(), target_kind, egen));
-                    target.energy_inc(egen);
+                        player, target_kind, egen));
+                    energy_inc(target, egen, carry_over, player, log);
                     Some(())
                 } else {
                     None
@@ -653,10 +1042,9 @@ impl Game {
     fn do_engineer(
         &mut self,
         player: PlayerPos,
-        log: &mut TurnLog,
+        log: &mut dyn ActionLog,
     ) -> Option<(PlayerPos, HeroPos, Bomb)>
     {
-        let id = self.get_player_id(player);
         self.get_hero_of_act_mut(player, HeroKind::Engineer)
             .map(|(hero, pos)| {
                 hero.set_act(false);
@@ -669,21 +1057,21 @@ impl Game {
                 // do damage
                 if opp.get_bulwark() > 0 {
                     log.push(Action::HeroDamage(
-                        id.clone(),
+                        player,
                         HeroKind::Engineer,
                         Damage::Bulwark(dmg_blwk)
                     ));
                     opp.bulwark_dec(dmg_blwk);
                 } else {
                     log.push(Action::HeroDamage(
-                        id.clone(),
+                        player,
                         HeroKind::Engineer,
                         Damage::Crown(dmg_crown)
                     ));
                     opp.crown_dec(dmg_crown);
                 }
                 // do bulwark build
-                log.push(Action::EngineerBuild(id.clone(), 2));
+                log.push(Action::EngineerBuild(player, 2));
                 self.get_player_mut(player).bulwark_inc(2);
                 // +2 EXP from acting
                 (player, pos, self.do_exp_level(player, pos, 2, log))
@@ -693,10 +1081,9 @@ impl Game {
     fn do_warrior(
         &mut self,
         player: PlayerPos,
-        log: &mut TurnLog,
+        log: &mut dyn ActionLog,
     ) -> Option<(PlayerPos, HeroPos, Bomb)>
     {
-        let id = self.get_player_id(player);
         self.get_hero_of_act_mut(player, HeroKind::Warrior)
             .map(|(hero, pos)| {
                 hero.set_act(false);
@@ -709,14 +1096,14 @@ impl Game {
                 // do damage
                 if opp.get_bulwark() > 0 {
                     log.push(Action::HeroDamage(
-                        id.clone(),
+                        player,
                         HeroKind::Warrior,
                         Damage::Bulwark(dmg_blwk)
                     ));
                     opp.bulwark_dec(dmg_blwk);
                 } else {
                     log.push(Action::HeroDamage(
-                        id.clone(),
+                        player,
                         HeroKind::Warrior,
                         Damage::Crown(dmg_crown)
                     ));
@@ -730,10 +1117,9 @@ impl Game {
     fn do_mage(
         &mut self,
         player: PlayerPos,
-        log: &mut TurnLog,
+        log: &mut dyn ActionLog,
     ) -> Option<(PlayerPos, HeroPos, Bomb)>
     {
-        let id = self.get_player_id(player);
         self.get_hero_of_act_mut(player, HeroKind::Mage)
             .map(|(hero, pos)| {
                 hero.set_act(false);
@@ -746,16 +1132,16 @@ impl Game {
                 // do first damage
                 if opp.get_bulwark() > 0 {
                     log.push(Action::HeroDamage(
-                        id.clone(), HeroKind::Mage, Damage::Bulwark(dmg_blwk)));
+                        player, HeroKind::Mage, Damage::Bulwark(dmg_blwk)));
                     opp.bulwark_dec(dmg_blwk);
                 } else {
                     log.push(Action::HeroDamage(
-                        id.clone(), HeroKind::Mage, Damage::Crown(dmg_crown)));
+                        player, HeroKind::Mage, Damage::Crown(dmg_crown)));
                     opp.crown_dec(dmg_crown);
                 }
                 // do second damage
                 log.push(Action::HeroDamage(
-                    id.clone(), HeroKind::Mage, Damage::Crown(dmg_crown)));
+                    player, HeroKind::Mage, Damage::Crown(dmg_crown)));
                 opp.crown_dec(dmg_crown);
                 // +2 EXP from acting
                 (player, pos, self.do_exp_level(player, pos, 2, log))
@@ -765,10 +1151,9 @@ impl Game {
     fn do_archer(
         &mut self,
         player: PlayerPos,
-        log: &mut TurnLog,
+        log: &mut dyn ActionLog,
     ) -> Option<(PlayerPos, HeroPos, Bomb)>
     {
-        let id = self.get_player_id(player);
         self.get_hero_of_act_mut(player, HeroKind::Archer)
             .map(|(hero, pos)| {
                 hero.set_act(false);
@@ -781,14 +1166,14 @@ impl Game {
                 // do damage
                 if opp.get_bulwark() > 2 {
                     log.push(Action::HeroDamage(
-                        id.clone(),
+                        player,
                         HeroKind::Archer,
                         Damage::Bulwark(dmg_blwk)
                     ));
                     opp.bulwark_dec(dmg_blwk);
                 } else {
                     log.push(Action::HeroDamage(
-                        id.clone(),
+                        player,
                         HeroKind::Archer,
                         Damage::Crown(dmg_crown)
                     ));
@@ -803,21 +1188,21 @@ impl Game {
         &mut self,
         player: PlayerPos,
         bomb: &mut Bomb,
-        log: &mut TurnLog,
+        log: &mut dyn ActionLog,
     ) {
-        let id = self.get_player_id(player);
         match *bomb {
             Bomb::Yes => {
+                let dmg = self.get_bomb_dmg(self.turn);
                 let opp = self.get_player_mut(player.other());
-                log.push(Action::Bomb(id, 2));
-                opp.crown_dec(2);
+                log.push(Action::Bomb(player, dmg));
+                opp.crown_dec(dmg);
                 *bomb = Bomb::No;
             },
             Bomb::No => { },
         }
     }
 
-    fn do_bombs(&mut self, bombs: &mut Bombs, log: &mut TurnLog) {
+    fn do_bombs(&mut self, bombs: &mut Bombs, log: &mut dyn ActionLog) {
         use PlayerPos::*;
         use HeroPos::*;
         self.do_bomb(P1, bombs.get_mut(P1, L), log);
@@ -826,125 +1211,265 @@ impl Game {
         self.do_bomb(P2, bombs.get_mut(P2, R), log);
     }
 
+    #[cfg(feature = "terminal")]
     pub fn do_turn(&mut self, rolls_p1: &Rolls, rolls_p2: &Rolls)
         -> (Option<Winner>, TurnLog)
+    {
+        self.do_turn_rng(rolls_p1, rolls_p2, &mut thread_rng())
+    }
+
+    /// Like [`Self::do_turn`], but drawing any random tie-breaks from `rng`.
+    pub fn do_turn_rng<R>(
+        &mut self,
+        rolls_p1: &Rolls,
+        rolls_p2: &Rolls,
+        rng: &mut R,
+    ) -> (Option<Winner>, TurnLog)
+    where R: Rng + ?Sized
+    {
+        self.do_turn_partial_rng(Some(rolls_p1), Some(rolls_p2), rng)
+    }
+
+    /// Like [`Self::do_turn`], but where a player without rolls sits out the
+    /// turn: none of their panels are counted, although their heroes may
+    /// still be acted on by the other player's.
+    #[cfg(feature = "terminal")]
+    pub fn do_turn_partial(
+        &mut self,
+        rolls_p1: Option<&Rolls>,
+        rolls_p2: Option<&Rolls>,
+    ) -> (Option<Winner>, TurnLog)
+    {
+        self.do_turn_partial_rng(rolls_p1, rolls_p2, &mut thread_rng())
+    }
+
+    /// Like [`Self::do_turn_partial`], but drawing any random tie-breaks from
+    /// `rng`.
+    pub fn do_turn_partial_rng<R>(
+        &mut self,
+        rolls_p1: Option<&Rolls>,
+        rolls_p2: Option<&Rolls>,
+        rng: &mut R,
+    ) -> (Option<Winner>, TurnLog)
+    where R: Rng + ?Sized
+    {
+        let mut log: TurnLog = Vec::new();
+        let winner
+            = self.do_turn_partial_log(rolls_p1, rolls_p2, rng, &mut log);
+        (winner, log)
+    }
+
+    /// Like [`Self::do_turn_partial_rng`], but sending every action to `log`
+    /// instead of a new [`TurnLog`].
+    ///
+    /// Pass [`NoLog`] to skip logging entirely, or the same [`TurnLog`] every
+    /// turn (cleared in between) to avoid allocating a new one each time.
+    pub fn do_turn_partial_log<R>(
+        &mut self,
+        rolls_p1: Option<&Rolls>,
+        rolls_p2: Option<&Rolls>,
+        rng: &mut R,
+        log: &mut dyn ActionLog,
+    ) -> Option<Winner>
+    where R: Rng + ?Sized
     {
         use PlayerPos::*;
         use HeroPos::*;
 
-        let mut log: TurnLog = Vec::new();
-        let log_ref = &mut log;
-        let totals_p1 = Wheel::totals(rolls_p1);
-        let totals_p2 = Wheel::totals(rolls_p2);
+        self.turn += 1;
+        let totals_p1 = rolls_p1.map(Wheel::totals).unwrap_or_default();
+        let totals_p2 = rolls_p2.map(Wheel::totals).unwrap_or_default();
 
         //  1 Panel XP, Level ups
-        let p1l = self.do_exp_level(P1, L, totals_p1.exp_l, log_ref);
-        let p1r = self.do_exp_level(P1, R, totals_p1.exp_r, log_ref);
-        let p2l = self.do_exp_level(P2, L, totals_p2.exp_l, log_ref);
-        let p2r = self.do_exp_level(P2, R, totals_p2.exp_r, log_ref);
+        let p1l = self.do_exp_level(P1, L, totals_p1.exp_l, log);
+        let p1r = self.do_exp_level(P1, R, totals_p1.exp_r, log);
+        let p2l = self.do_exp_level(P2, L, totals_p2.exp_l, log);
+        let p2r = self.do_exp_level(P2, R, totals_p2.exp_r, log);
         let mut bombs = Bombs { p1l, p1r, p2l, p2r };
 
+        self.check_phase("panel EXP");
+
         //  2 Hammer panels added
-        self.do_bulwark(P1, totals_p1.hammers, log_ref);
-        self.do_bulwark(P2, totals_p2.hammers, log_ref);
+        self.do_bulwark(P1, totals_p1.hammers, log);
+        self.do_bulwark(P2, totals_p2.hammers, log);
+
+        self.check_phase("hammer panels");
 
         //  3 Energy panels added
-        let p1l = self.do_energy(P1, L, totals_p1.squares, log_ref);
-        let p1r = self.do_energy(P1, R, totals_p1.diamonds, log_ref);
-        let p2l = self.do_energy(P2, L, totals_p2.squares, log_ref);
-        let p2r = self.do_energy(P2, R, totals_p2.diamonds, log_ref);
+        let p1l = self.do_energy(P1, L, totals_p1.squares, log);
+        let p1r = self.do_energy(P1, R, totals_p1.diamonds, log);
+        let p2l = self.do_energy(P2, L, totals_p2.squares, log);
+        let p2r = self.do_energy(P2, R, totals_p2.diamonds, log);
         let first_acts = Acts { p1l, p1r, p2l, p2r };
 
+        self.check_phase("energy panels");
+
         //  4 Assassin Acts
-        bombs.or_mut(self.do_assassin(P1, log_ref));
-        bombs.or_mut(self.do_assassin(P2, log_ref));
+        bombs.or_mut(self.do_assassin(P1, log, rng));
+        bombs.or_mut(self.do_assassin(P2, log, rng));
+
+        self.check_phase("Assassin");
 
         //  5 Priest heals + (If the second hero does not have enough energy
         //    from energy panels to act: Priest grants energy) + Action XP
-        bombs.or_mut(self.do_priest(P1, log_ref));
-        bombs.or_mut(self.do_priest(P2, log_ref));
+        bombs.or_mut(self.do_priest(P1, log));
+        bombs.or_mut(self.do_priest(P2, log));
+
+        self.check_phase("Priest");
 
         //  6 Engineer Acts
-        bombs.or_mut(self.do_engineer(P1, log_ref));
-        bombs.or_mut(self.do_engineer(P2, log_ref));
+        bombs.or_mut(self.do_engineer(P1, log));
+        bombs.or_mut(self.do_engineer(P2, log));
+
+        self.check_phase("Engineer");
 
         //  7 Bombs
-        self.do_bombs(&mut bombs, log_ref);
+        self.do_bombs(&mut bombs, log);
+
+        self.check_phase("bombs");
 
         //  8 Rest of heroes act
-        bombs.or_mut(self.do_warrior(P1, log_ref));
-        bombs.or_mut(self.do_warrior(P2, log_ref));
-        bombs.or_mut(self.do_mage(P1, log_ref));
-        bombs.or_mut(self.do_mage(P2, log_ref));
-        bombs.or_mut(self.do_archer(P1, log_ref));
-        bombs.or_mut(self.do_archer(P2, log_ref));
+        bombs.or_mut(self.do_warrior(P1, log));
+        bombs.or_mut(self.do_warrior(P2, log));
+        bombs.or_mut(self.do_mage(P1, log));
+        bombs.or_mut(self.do_mage(P2, log));
+        bombs.or_mut(self.do_archer(P1, log));
+        bombs.or_mut(self.do_archer(P2, log));
+
+        self.check_phase("other heroes");
 
         //  9 (If the second hero had enough energy from energy panels to act:
         //    Priest grants energy)
-        self.do_priest_second(P1, first_acts, log_ref);
-        self.do_priest_second(P2, first_acts, log_ref);
+        self.do_priest_second(P1, first_acts, log);
+        self.do_priest_second(P2, first_acts, log);
+
+        self.check_phase("Priest energy");
 
         // 10 Hero acts from priest energy
-        bombs.or_mut(self.do_assassin(P1, log_ref));
-        bombs.or_mut(self.do_assassin(P2, log_ref));
-        bombs.or_mut(self.do_engineer(P1, log_ref));
-        bombs.or_mut(self.do_engineer(P2, log_ref));
-        bombs.or_mut(self.do_warrior(P1, log_ref));
-        bombs.or_mut(self.do_warrior(P2, log_ref));
-        bombs.or_mut(self.do_mage(P1, log_ref));
-        bombs.or_mut(self.do_mage(P2, log_ref));
-        bombs.or_mut(self.do_archer(P1, log_ref));
-        bombs.or_mut(self.do_archer(P2, log_ref));
+        bombs.or_mut(self.do_assassin(P1, log, rng));
+        bombs.or_mut(self.do_assassin(P2, log, rng));
+        bombs.or_mut(self.do_engineer(P1, log));
+        bombs.or_mut(self.do_engineer(P2, log));
+        bombs.or_mut(self.do_warrior(P1, log));
+        bombs.or_mut(self.do_warrior(P2, log));
+        bombs.or_mut(self.do_mage(P1, log));
+        bombs.or_mut(self.do_mage(P2, log));
+        bombs.or_mut(self.do_archer(P1, log));
+        bombs.or_mut(self.do_archer(P2, log));
+
+        self.check_phase("acts from Priest energy");
 
         // 11 Bombs (if deployed after a priest caused hero to act and gain XP)
-        self.do_bombs(&mut bombs, log_ref);
+        self.do_bombs(&mut bombs, log);
+
+        self.check_phase("bombs from Priest energy");
 
         // 12 0 HP Crown check (simultaneous)
         let p1_crown = self.get_player(P1).get_crown();
         let p2_crown = self.get_player(P2).get_crown();
         match (p1_crown, p2_crown) {
-            (0, 0) => (Some(Winner::Draw), log),
-            (0, _) => (Some(Winner::P2), log),
-            (_, 0) => (Some(Winner::P1), log),
-            _ => (None, log),
+            (0, 0) => Some(Winner::Draw(EndReason::Crown)),
+            (0, _) => Some(Winner::P2(EndReason::Crown)),
+            (_, 0) => Some(Winner::P1(EndReason::Crown)),
+            _ => {
+                self.rules.max_turns
+                    .is_some_and(|max| self.turn >= max)
+                    .then(|| self.tie_break(EndReason::TurnLimit))
+            },
         }
     }
 
-    fn display_rolls(rolls: &Rolls) {
-        println_flush!("┌─────┐┌─────┐┌─────┐┌─────┐┌─────┐");
-        println_flush!("│ {:^3} ││ {:^3} ││ {:^3} ││ {:^3} ││ {:^3} │",
-            rolls[0], rolls[1], rolls[2], rolls[3], rolls[4]);
-        println_flush!("└──1──┘└──2──┘└──3──┘└──4──┘└──5──┘");
+    /// Decide a game that did not end by Crown, by higher Crown and then
+    /// higher Bulwark.
+    fn tie_break(&self, reason: fn(TieBreak) -> EndReason) -> Winner {
+        let p1 = self.get_player(PlayerPos::P1);
+        let p2 = self.get_player(PlayerPos::P2);
+        let crown = p1.get_crown().cmp(&p2.get_crown());
+        let bulwark = p1.get_bulwark().cmp(&p2.get_bulwark());
+        match (crown, bulwark) {
+            (Ordering::Greater, _) => Winner::P1(reason(TieBreak::Crown)),
+            (Ordering::Less, _) => Winner::P2(reason(TieBreak::Crown)),
+            (_, Ordering::Greater) => Winner::P1(reason(TieBreak::Bulwark)),
+            (_, Ordering::Less) => Winner::P2(reason(TieBreak::Bulwark)),
+            _ => Winner::Draw(reason(TieBreak::Tied)),
+        }
     }
 
-    fn parse_lock_numbers(input: &str)
-        -> impl Iterator<Item = Result<usize, String>> + '_
-    {
-        input.split(',')
-            .filter_map(|sp| {
-                let trimmed = sp.trim();
-                (!trimmed.is_empty()).then_some(trimmed)
+    /// Damage dealt by a bomb on turn number `turn`, escalating by 1 per turn
+    /// past the start of sudden death.
+    fn get_bomb_dmg(&self, turn: usize) -> u8 {
+        let extra
+            = self.rules.sudden_death
+            .map(|start| turn.saturating_sub(start))
+            .unwrap_or(0);
+        BOMB_DMG.saturating_add(extra.min(u8::MAX as usize) as u8)
+    }
+
+    /// Draw each wheel in a box, with locked wheels in double lines.
+    fn display_rolls(rolls: &Rolls, locks: &[bool; 5]) {
+        let top: String
+            = locks.iter()
+            .map(|lock| if *lock { "╔═════╗" } else { "┌─────┐" })
+            .collect();
+        let mid: String
+            = rolls.iter().zip(locks.iter())
+            .map(|(wheel, lock)| {
+                if *lock {
+                    format!("║ {:^3} ║", wheel)
+                } else {
+                    format!("│ {:^3} │", wheel)
+                }
             })
-            .map(|k_str| {
-                k_str.parse::<usize>()
-                    .map_err(|_| {
-                        format!("failed to parse input '{}'", k_str.trim())
-                    })
-                    .and_then(|k| {
-                        if !(1..=5).contains(&k) {
-                            Err(format!("invalid input '{}': must be 1-5", k))
-                        } else {
-                            Ok(k)
-                        }
-                    })
+            .collect();
+        let bot: String
+            = locks.iter().enumerate()
+            .map(|(k, lock)| {
+                if *lock {
+                    format!("╚══{}══╝", k + 1)
+                } else {
+                    format!("└──{}──┘", k + 1)
+                }
             })
+            .collect();
+        println_flush!("{}", top);
+        println_flush!("{}", mid);
+        println_flush!("{}", bot);
     }
 
-    fn get_rolls_response(&self, rolls: &Rolls, locks: &mut [bool; 5]) {
-        locks.iter_mut().for_each(|lock| { *lock = false; });
-        Self::display_rolls(rolls);
+    fn display_advice(
+        &self,
+        player: PlayerPos,
+        rolls: &Rolls,
+        locks: &[bool; 5],
+        spins_left: usize,
+    ) {
+        let plr = self.get_player(player);
+        let hero_l = plr.get_hero(HeroPos::L);
+        let hero_r = plr.get_hero(HeroPos::R);
+        let locked: [bool; 5]
+            = if self.rules.permanent_locks { *locks } else { [false; 5] };
+        let advice = advisor::advise_locked(plr, rolls, spins_left, &locked);
+        let outlook = advice.outlook;
+        println_flush!("Advisor: {}", advice.describe(locks));
+        println_flush!("  {:<8} {:>3.0}% to act ({} energy left)",
+            hero_l.get_kind(), 100.0 * outlook.act_l, hero_l.get_energy_left());
+        println_flush!("  {:<8} {:>3.0}% to act ({} energy left)",
+            hero_r.get_kind(), 100.0 * outlook.act_r, hero_r.get_energy_left());
+        println_flush!("  expected Bulwark gain: {:.2}", outlook.bulwark);
+        println_flush!("  expected EXP: {:.2} ({}) / {:.2} ({})",
+            outlook.exp_l, hero_l.get_kind(), outlook.exp_r, hero_r.get_kind());
+    }
+
+    #[cfg(feature = "terminal")]
+    fn get_rolls_response(
+        &self,
+        player: PlayerPos,
+        rolls: &Rolls,
+        locks: &mut [bool; 5],
+        spins_left: usize,
+    ) {
+        Self::display_rolls(rolls, locks);
         let mut input: String;
-        let mut lock_numbers: Result<Vec<usize>, String>;
         let stdin = io::stdin();
         loop {
             input = String::new();
@@ -956,98 +1481,129 @@ impl Game {
                     continue;
                 },
             }
-            lock_numbers = Self::parse_lock_numbers(&input).collect();
-            match lock_numbers {
-                Ok(nums) => {
-                    nums.into_iter().for_each(|k| { locks[k - 1] = true; });
-                    break;
-                },
-                Err(msg) => {
-                    println_flush!("{}", msg);
+            if input.trim() == "?" {
+                self.display_advice(player, rolls, locks, spins_left);
+                continue;
+            }
+            let permanent = self.rules.permanent_locks;
+            match locks::apply(&input, rolls, locks, permanent) {
+                Ok(()) => { break; },
+                Err(e) => {
+                    println_flush!("{}", e.render(&input));
                     continue;
                 },
             }
         }
     }
 
-    fn get_rolls_response_cpu(&self, rolls: &Rolls, locks: &mut [bool; 5]) {
-        locks.iter_mut().for_each(|lock| { *lock = false; });
-        let plr = self.get_player(PlayerPos::P2);
-        let energy_left_l = plr.get_hero(HeroPos::L).get_energy_left();
-        let energy_left_r = plr.get_hero(HeroPos::R).get_energy_left();
-        let totals = Wheel::totals(rolls);
-        let target
-            = if (1..=2).contains(&energy_left_l) {
-                WheelKind::Square
-            } else if (1..=2).contains(&energy_left_r) {
-                WheelKind::Diamond
-            } else {
-                totals.max_kind()
-            };
-        rolls.iter()
-            .zip(locks.iter_mut())
-            .for_each(|(wheel, lock)| {
-                if wheel.get_kind() == target {
-                    *lock = true;
-                }
-            });
+    #[cfg(feature = "terminal")]
+    fn get_rolls_response_cpu(
+        &self,
+        player: PlayerPos,
+        rolls: &Rolls,
+        locks: &mut [bool; 5],
+        spins_left: usize,
+    ) {
+        let plr = self.get_player(player);
+        Strategy::Classic.get_locks(
+            plr,
+            rolls,
+            spins_left,
+            locks,
+            self.rules.permanent_locks,
+            &mut thread_rng(),
+        );
     }
 
-    pub fn get_rolls(&self, turn_counter: usize) -> (Rolls, Rolls) {
+    #[cfg(feature = "terminal")]
+    pub fn get_rolls(&mut self, turn_counter: usize) -> (Rolls, Rolls) {
         let mut rng = thread_rng();
         let mut p1_rolls = Wheel::gen_rolls(&mut rng);
-        let mut p1_locks = [false; 5];
         let mut p2_rolls = Wheel::gen_rolls(&mut rng);
-        let mut p2_locks = [false; 5];
-        if turn_counter % 2 == 1 {
-            println_flush!("Player 1:");
-            sleep(500);
-            self.get_rolls_response(&p1_rolls, &mut p1_locks);
-            Wheel::gen_rolls_locked(&mut p1_rolls, &p1_locks, &mut rng);
-            sleep(500);
-            self.get_rolls_response(&p1_rolls, &mut p1_locks);
-            Wheel::gen_rolls_locked(&mut p1_rolls, &p1_locks, &mut rng);
-            sleep(500);
-            Self::display_rolls(&p1_rolls);
-        } else {
-            println_flush!("Player 2:");
-            sleep(500);
-            self.get_rolls_response(&p2_rolls, &mut p2_locks);
-            Wheel::gen_rolls_locked(&mut p2_rolls, &p2_locks, &mut rng);
-            sleep(500);
-            self.get_rolls_response(&p2_rolls, &mut p2_locks);
-            Wheel::gen_rolls_locked(&mut p2_rolls, &p2_locks, &mut rng);
+        let (player, rolls)
+            = if turn_counter % 2 == 1 {
+                println_flush!("Player 1:");
+                (PlayerPos::P1, &mut p1_rolls)
+            } else {
+                println_flush!("Player 2:");
+                (PlayerPos::P2, &mut p2_rolls)
+            };
+        let mut locks = [false; 5];
+        let respins = self.get_player(player).get_handicap().get_respins();
+        for spins_left in (1..=respins).rev() {
             sleep(500);
-            Self::display_rolls(&p2_rolls);
+            self.get_rolls_response(player, rolls, &mut locks, spins_left);
+            self.record_spin(player, rolls, &locks, spins_left);
+            Wheel::gen_rolls_locked(rolls, &locks, &mut rng);
         }
+        sleep(500);
+        Self::display_rolls(rolls, &locks);
         (p1_rolls, p2_rolls)
     }
 
-    pub fn get_rolls_single(&self) -> Rolls {
+    #[cfg(feature = "terminal")]
+    pub fn get_rolls_single(&mut self, player: PlayerPos) -> Rolls {
         let mut rng = thread_rng();
         let mut rolls = Wheel::gen_rolls(&mut rng);
         let mut locks = [false; 5];
-        self.get_rolls_response(&rolls, &mut locks);
-        Wheel::gen_rolls_locked(&mut rolls, &locks, &mut rng);
-        self.get_rolls_response(&rolls, &mut locks);
-        Wheel::gen_rolls_locked(&mut rolls, &locks, &mut rng);
-        Self::display_rolls(&rolls);
+        let respins = self.get_player(player).get_handicap().get_respins();
+        for spins_left in (1..=respins).rev() {
+            self.get_rolls_response(player, &rolls, &mut locks, spins_left);
+            self.record_spin(player, &rolls, &locks, spins_left);
+            Wheel::gen_rolls_locked(&mut rolls, &locks, &mut rng);
+        }
+        Self::display_rolls(&rolls, &locks);
         rolls
     }
 
-    pub fn get_rolls_cpu(&self) -> Rolls {
+    /// Generate a final set of rolls for the computer playing `player`,
+    /// along with the locks it chose on the last spin.
+    #[cfg(feature = "terminal")]
+    pub fn get_rolls_cpu(&mut self, player: PlayerPos) -> (Rolls, [bool; 5]) {
         let mut rng = thread_rng();
         let mut rolls = Wheel::gen_rolls(&mut rng);
         let mut locks = [false; 5];
-        self.get_rolls_response_cpu(&rolls, &mut locks);
-        Wheel::gen_rolls_locked(&mut rolls, &locks, &mut rng);
-        self.get_rolls_response_cpu(&rolls, &mut locks);
-        Wheel::gen_rolls_locked(&mut rolls, &locks, &mut rng);
+        let respins = self.get_player(player).get_handicap().get_respins();
+        for spins_left in (1..=respins).rev() {
+            self.get_rolls_response_cpu(player, &rolls, &mut locks, spins_left);
+            self.record_spin(player, &rolls, &locks, spins_left);
+            Wheel::gen_rolls_locked(&mut rolls, &locks, &mut rng);
+        }
+        (rolls, locks)
+    }
+
+    /// Generate a final set of rolls for `player`, choosing locks with
+    /// `strategy`.
+    pub fn get_rolls_strategy<R>(
+        &self,
+        player: PlayerPos,
+        strategy: Strategy,
+        rng: &mut R,
+    ) -> Rolls
+    where R: Rng + ?Sized
+    {
+        let plr = self.get_player(player);
+        let mut rolls = Wheel::gen_rolls(rng);
+        let mut locks = [false; 5];
+        let permanent = self.rules.permanent_locks;
+        for spins_left in (1..=plr.get_handicap().get_respins()).rev() {
+            strategy.get_locks(
+                plr, &rolls, spins_left, &mut locks, permanent, rng);
+            Wheel::gen_rolls_locked(&mut rolls, &locks, rng);
+        }
         rolls
     }
 
     fn display_turn(&self, turn: usize) {
-        let turn_str = format!("Turn {}", turn);
+        let mut turn_str
+            = match self.rules.max_turns {
+                Some(max) => format!("Turn {} of {}", turn, max),
+                None => format!("Turn {}", turn),
+            };
+        if self.rules.sudden_death.is_some_and(|start| turn > start) {
+            turn_str += &format!(
+                " (sudden death: bombs deal {})", self.get_bomb_dmg(turn));
+        }
         let turn_str_len = turn_str.len() + 2;
         let left: usize = (DISPW - turn_str_len) / 2;
         let right: usize = DISPW - left - turn_str_len;
@@ -1070,6 +1626,25 @@ impl Game {
             p1.get_crown(), p2.get_crown(), w=TEXTW - 7);
         println_flush!("│ Bulwark: {:<w$} ││ Bulwark: {:<w$} │",
             p1.get_bulwark(), p2.get_bulwark(), w=TEXTW - 9);
+        if let Some(meter) = self.meter.as_ref() {
+            println_flush!("│ Win chance: {:<w$} ││ Win chance: {:<w$} │",
+                meter.label(PlayerPos::P1), meter.label(PlayerPos::P2),
+                w=TEXTW - 12);
+        }
+        if !p1.get_handicap().is_none() || !p2.get_handicap().is_none() {
+            let lines_p1
+                = wrap_words(&p1.get_handicap().to_string(), TEXTW - 10);
+            let lines_p2
+                = wrap_words(&p2.get_handicap().to_string(), TEXTW - 10);
+            for k in 0..lines_p1.len().max(lines_p2.len()) {
+                let label = if k == 0 { "Handicap:" } else { "" };
+                println_flush!("│ {:<9} {:<w$} ││ {:<9} {:<w$} │",
+                    label, lines_p1.get(k).map_or("", String::as_str),
+                    label, lines_p2.get(k).map_or("", String::as_str),
+                    w=TEXTW - 10,
+                );
+            }
+        }
 
         println_flush!("│ Left: {:<w$} ││ Left: {:<w$} │",
             p1l.get_kind(), p2l.get_kind(), w=TEXTW - 6);
@@ -1097,64 +1672,187 @@ impl Game {
         println_flush!("└{bot}┘└{bot}┘", bot="─".repeat(TEXTW + 2));
     }
 
+    #[cfg(feature = "terminal")]
     fn display_log(&self, log: TurnLog) {
         for action in log.iter() {
             sleep(500);
-            println_flush!("> {}", action.msg());
+            println_flush!("> {}", action.msg(self));
         }
     }
 
+    #[cfg(feature = "terminal")]
     pub fn run(&mut self) -> Winner {
-        let mut turn_counter: usize = 0;
         let mut rolls: (Rolls, Rolls);
+        self.stats = Some(MatchStats::new(self));
         loop {
-            turn_counter += 1;
-            self.display_turn(turn_counter);
+            // the game may have been started partway through
+            let turn = self.turn + 1;
+            self.display_turn(turn);
+            self.update_win_meter();
             self.display_board();
-            rolls = self.get_rolls(turn_counter);
-            match self.do_turn(&rolls.0, &rolls.1) {
-                (Some(winner), turn_log) => {
-                    self.display_log(turn_log);
-                    return winner;
-                },
-                (None, turn_log) => {
-                    self.display_log(turn_log);
-                    continue;
-                },
+            self.record_turn(turn);
+            rolls = self.get_rolls(turn);
+            let p1_active = self.is_active(PlayerPos::P1, turn);
+            let p2_active = self.is_active(PlayerPos::P2, turn);
+            let p1_rolls = p1_active.then_some(&rolls.0);
+            let p2_rolls = p2_active.then_some(&rolls.1);
+            if let Some(r) = p1_rolls { self.record_rolls(PlayerPos::P1, r); }
+            if let Some(r) = p2_rolls { self.record_rolls(PlayerPos::P2, r); }
+            let (winner, turn_log) = self.do_turn_partial(p1_rolls, p2_rolls);
+            self.record_stats(p1_rolls, p2_rolls, &turn_log);
+            self.display_log(turn_log);
+            if let Some(winner) = winner {
+                self.record_winner(winner);
+                return winner;
             }
         }
     }
 
+    /// Play a game with no display or input, with each player choosing locks
+    /// according to a strategy.
+    ///
+    /// Returns the winner and the number of turns played. Games lasting longer
+    /// than [`HEADLESS_TURN_LIMIT`] turns are called a draw, unless the rules
+    /// set a lower turn limit. No actions are logged.
+    pub fn run_headless<R>(
+        &mut self,
+        strategy_p1: Strategy,
+        strategy_p2: Strategy,
+        rng: &mut R,
+    ) -> (Winner, usize)
+    where R: Rng + ?Sized
+    {
+        let mut p1_rolls: Option<Rolls>;
+        let mut p2_rolls: Option<Rolls>;
+        for turn_counter in 1..=HEADLESS_TURN_LIMIT {
+            // the game may have been started partway through
+            let turn = self.turn + 1;
+            p1_rolls
+                = self.is_active(PlayerPos::P1, turn)
+                .then(|| {
+                    self.get_rolls_strategy(PlayerPos::P1, strategy_p1, rng)
+                });
+            p2_rolls
+                = self.is_active(PlayerPos::P2, turn)
+                .then(|| {
+                    self.get_rolls_strategy(PlayerPos::P2, strategy_p2, rng)
+                });
+            if let Some(winner)
+                = self.do_turn_partial_log(
+                    p1_rolls.as_ref(), p2_rolls.as_ref(), rng, &mut NoLog)
+            {
+                return (winner, turn_counter);
+            }
+        }
+        (Winner::Draw(EndReason::Abandoned), HEADLESS_TURN_LIMIT)
+    }
+
+    #[cfg(feature = "terminal")]
     pub fn run_singleplayer(&mut self) -> Winner {
-        let mut turn_counter: usize = 0;
-        let mut p1_rolls: Rolls;
-        let mut p2_rolls: Rolls;
+        self.run_vs_cpu(PlayerPos::P1)
+    }
+
+    /// Play a game where `human` is played from the terminal and the other
+    /// player by the computer.
+    #[cfg(feature = "terminal")]
+    pub fn run_vs_cpu(&mut self, human: PlayerPos) -> Winner {
+        let cpu = human.other();
+        self.stats = Some(MatchStats::new(self));
+        let mut human_rolls: Option<Rolls>;
+        let mut cpu_rolls: Option<Rolls>;
         loop {
-            turn_counter += 1;
+            // the game may have been started partway through
+            let turn = self.turn + 1;
             println_flush!();
-            self.display_turn(turn_counter);
+            self.display_turn(turn);
+            self.update_win_meter();
             self.display_board();
-            p1_rolls = self.get_rolls_single();
-            p2_rolls = self.get_rolls_cpu();
-            sleep(1000);
-            println_flush!("\nCPU's rolls:");
-            Self::display_rolls(&p2_rolls);
-            println_flush!();
-            match self.do_turn(&p1_rolls, &p2_rolls) {
-                (Some(winner), turn_log) => {
-                    self.display_log(turn_log);
-                    return winner;
-                },
-                (None, turn_log) => {
-                    self.display_log(turn_log);
-                    continue;
-                },
+            self.record_turn(turn);
+            human_rolls
+                = self.is_active(human, turn)
+                .then(|| self.get_rolls_single(human));
+            let cpu_spin
+                = self.is_active(cpu, turn)
+                .then(|| self.get_rolls_cpu(cpu));
+            cpu_rolls = cpu_spin.map(|(rolls, _)| rolls);
+            if let Some(r) = human_rolls.as_ref() {
+                self.record_rolls(human, r);
+            }
+            if let Some((r, locks)) = cpu_spin.as_ref() {
+                self.record_rolls(cpu, r);
+                sleep(1000);
+                println_flush!("\nCPU's rolls:");
+                Self::display_rolls(r, locks);
+                println_flush!();
+            }
+            let (p1_rolls, p2_rolls)
+                = match human {
+                    PlayerPos::P1 => (human_rolls.as_ref(), cpu_rolls.as_ref()),
+                    PlayerPos::P2 => (cpu_rolls.as_ref(), human_rolls.as_ref()),
+                };
+            let (winner, turn_log) = self.do_turn_partial(p1_rolls, p2_rolls);
+            self.record_stats(p1_rolls, p2_rolls, &turn_log);
+            self.display_log(turn_log);
+            if let Some(winner) = winner {
+                self.record_winner(winner);
+                return winner;
             }
         }
     }
 }
 
+#[cfg(feature = "terminal")]
 fn sleep(ms: u64) {
     thread::sleep(time::Duration::from_millis(ms));
 }
 
+/// Add EXP to `hero`, returning the amount carried over to the next level if
+/// it levels up.
+fn exp_inc(hero: &mut Hero, inc: u8, carry_over: bool) -> Option<u8> {
+    if carry_over {
+        hero.exp_inc_carry(inc)
+    } else {
+        hero.exp_inc(inc).then_some(0)
+    }
+}
+
+/// Add energy to `hero`, logging any carried over to the next rod.
+fn energy_inc(
+    hero: &mut Hero,
+    inc: u8,
+    carry_over: bool,
+    player: PlayerPos,
+    log: &mut dyn ActionLog,
+) -> Act
+{
+    let carry
+        = if carry_over {
+            hero.energy_inc_carry(inc)
+        } else {
+            hero.energy_inc(inc).then_some(0)
+        };
+    match carry {
+        Some(carry) => {
+            if carry > 0 {
+                log.push(Action::EnergyCarry(player, hero.get_kind(), carry));
+            }
+            Act::Yes
+        },
+        None => Act::No,
+    }
+}
+
+/// Split `s` into lines of at most `width` characters, breaking between words.
+fn wrap_words(s: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for word in s.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if line.len() + 1 + word.len() <= width => {
+                line.push(' ');
+                line.push_str(word);
+            },
+            _ => { lines.push(word.to_string()); },
+        }
+    }
+    lines
+}
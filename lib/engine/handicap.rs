@@ -0,0 +1,207 @@
+#[cfg(feature = "terminal")]
+use std::io;
+#[cfg(feature = "terminal")]
+use crate::{ print_flush, println_flush };
+use crate::engine::{
+    hero::{ MAX_LEVEL, MAX_EXP },
+    player::{ INIT_CROWN, MAX_CROWN, INIT_BULWARK, MAX_BULWARK },
+};
+
+/// Number of re-spins each player gets per turn without a handicap.
+pub const RESPINS: usize = 2;
+
+/// Starting advantages given to a player, e.g. to even out a game between a
+/// newer player and a veteran.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Handicap {
+    /// Starting Crown.
+    pub crown: u8,
+    /// Starting Bulwark.
+    pub bulwark: u8,
+    /// Starting level of both heroes.
+    pub level: u8,
+    /// Starting EXP of both heroes.
+    pub exp: u8,
+    /// Re-spins per turn on top of the usual [`RESPINS`].
+    pub spins: u8,
+}
+
+impl Default for Handicap {
+    fn default() -> Self {
+        Self {
+            crown: INIT_CROWN,
+            bulwark: INIT_BULWARK,
+            level: 0,
+            exp: 0,
+            spins: 0,
+        }
+    }
+}
+
+impl Handicap {
+    #[cfg(feature = "terminal")]
+    pub(crate) fn get_choose() -> Self {
+        let stdin = io::stdin();
+        println_flush!(
+            "Handicap, e.g. `crown=12, spins=1`, or ENTER for none\n\
+            (keys: crown, bulwark, level, exp, spins):"
+        );
+        loop {
+            let mut input = String::new();
+            print_flush!(">>> ");
+            match stdin.read_line(&mut input) {
+                Ok(_) => { },
+                Err(e) => {
+                    println_flush!("error reading input: {}", e);
+                    continue;
+                },
+            }
+            match input.parse() {
+                Ok(handicap) => { break handicap; },
+                Err(e) => {
+                    println_flush!("{}", e);
+                    continue;
+                },
+            }
+        }
+    }
+
+    /// Whether this is no handicap at all.
+    pub fn is_none(&self) -> bool { *self == Self::default() }
+
+    /// Number of re-spins per turn.
+    pub fn get_respins(&self) -> usize { RESPINS + self.spins as usize }
+
+    /// Check that the handicap describes a legal starting position.
+    pub fn check(&self) -> Result<(), String> {
+        if !(1..=MAX_CROWN).contains(&self.crown) {
+            return Err(format!("crown must be 1-{}, got {}",
+                MAX_CROWN, self.crown));
+        }
+        if self.bulwark > MAX_BULWARK {
+            return Err(format!("bulwark must be at most {}, got {}",
+                MAX_BULWARK, self.bulwark));
+        }
+        if self.level > MAX_LEVEL {
+            return Err(format!("level must be at most {}, got {}",
+                MAX_LEVEL, self.level));
+        }
+        if self.exp >= MAX_EXP {
+            return Err(format!("exp must be less than {}, got {}",
+                MAX_EXP, self.exp));
+        }
+        Ok(())
+    }
+}
+
+/// Written as `key=value` pairs for every field that differs from no
+/// handicap, e.g. `crown=12 spins=1`, or `none`.
+impl std::fmt::Display for Handicap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let none = Self::default();
+        let fields: Vec<String>
+            = [
+                ("crown", self.crown, none.crown),
+                ("bulwark", self.bulwark, none.bulwark),
+                ("level", self.level, none.level),
+                ("exp", self.exp, none.exp),
+                ("spins", self.spins, none.spins),
+            ]
+            .into_iter()
+            .filter(|(_, value, default)| value != default)
+            .map(|(key, value, _)| format!("{}={}", key, value))
+            .collect();
+        if fields.is_empty() {
+            "none".fmt(f)
+        } else {
+            fields.join(" ").fmt(f)
+        }
+    }
+}
+
+/// Parses `key=value` pairs separated by commas or whitespace, where keys are
+/// any of `crown`, `bulwark`, `level`, `exp` and `spins`. An empty string or
+/// `none` is no handicap.
+impl std::str::FromStr for Handicap {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut handicap = Self::default();
+        if s.trim() == "none" { return Ok(handicap); }
+        let pairs
+            = s.split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|pair| !pair.is_empty());
+        for pair in pairs {
+            let (key, value)
+                = pair.split_once('=')
+                .ok_or_else(|| {
+                    format!("expected `key=value`, got '{}'", pair)
+                })?;
+            let value: u8
+                = value.parse()
+                .map_err(|_| {
+                    format!("invalid value for {}: '{}'", key, value)
+                })?;
+            match key.to_ascii_lowercase().as_str() {
+                "crown" => { handicap.crown = value; },
+                "bulwark" => { handicap.bulwark = value; },
+                "level" => { handicap.level = value; },
+                "exp" => { handicap.exp = value; },
+                "spins" => { handicap.spins = value; },
+                _ => { return Err(format!("unknown handicap '{}'", key)); },
+            }
+        }
+        handicap.check()?;
+        Ok(handicap)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let handicap: Handicap = "crown=12, level=1 exp=3".parse().unwrap();
+        assert_eq!(
+            handicap,
+            Handicap { crown: 12, level: 1, exp: 3, ..Handicap::default() },
+        );
+        assert_eq!("".parse(), Ok(Handicap::default()));
+        assert_eq!("none".parse(), Ok(Handicap::default()));
+    }
+
+    #[test]
+    fn bad_handicaps() {
+        let err = |s: &str| s.parse::<Handicap>().unwrap_err();
+        assert_eq!(err("luck=3"), "unknown handicap 'luck'");
+        assert_eq!(err("crown 12"), "expected `key=value`, got 'crown'");
+        assert_eq!(err("crown=-1"), "invalid value for crown: '-1'");
+        assert_eq!(err("crown=0"), "crown must be 1-12, got 0");
+        assert_eq!(err("crown=13"), "crown must be 1-12, got 13");
+        assert_eq!(err("level=3"), "level must be at most 2, got 3");
+        assert_eq!(
+            err(&format!("exp={}", MAX_EXP)),
+            format!("exp must be less than {}, got {}", MAX_EXP, MAX_EXP),
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let handicaps = [
+            Handicap::default(),
+            Handicap { crown: 1, ..Handicap::default() },
+            Handicap {
+                crown: 12,
+                bulwark: MAX_BULWARK,
+                level: MAX_LEVEL,
+                exp: MAX_EXP - 1,
+                spins: 2,
+            },
+        ];
+        for handicap in handicaps {
+            assert_eq!(handicap.to_string().parse(), Ok(handicap));
+        }
+        assert_eq!(Handicap::default().to_string(), "none");
+    }
+}
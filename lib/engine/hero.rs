@@ -1,4 +1,6 @@
+#[cfg(feature = "terminal")]
 use std::io;
+#[cfg(feature = "terminal")]
 use crate::{ print_flush, println_flush };
 
 pub const MAX_LEVEL: u8 = 2;
@@ -24,6 +26,40 @@ pub enum HeroKind {
     Priest,
 }
 
+impl HeroKind {
+    /// All hero kinds, in menu order.
+    pub fn all() -> [Self; 6] {
+        [
+            Self::Warrior,
+            Self::Mage,
+            Self::Archer,
+            Self::Engineer,
+            Self::Assassin,
+            Self::Priest,
+        ]
+    }
+
+    /// Look up a hero kind by its (case-insensitive) name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::all().into_iter()
+            .find(|kind| kind.to_string().eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// Parse a pair of distinct heroes written as `left/right`, e.g.
+    /// `Warrior/Mage`.
+    pub fn pair_from_name(pair: &str) -> Result<(Self, Self), String> {
+        let invalid = || format!("invalid loadout '{}'", pair);
+        let (l, r) = pair.split_once('/').ok_or_else(invalid)?;
+        let hero = |name: &str| -> Result<Self, String> {
+            Self::from_name(name)
+                .ok_or_else(|| format!("unknown hero '{}'", name))
+        };
+        let (l, r) = (hero(l)?, hero(r)?);
+        if l == r { return Err(invalid()); }
+        Ok((l, r))
+    }
+}
+
 impl std::fmt::Display for HeroKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         format!("{:?}", self).fmt(f)
@@ -31,6 +67,7 @@ impl std::fmt::Display for HeroKind {
 }
 
 impl Hero {
+    #[cfg(feature = "terminal")]
     pub(crate) fn get_choose() -> Self {
         let stdin = io::stdin();
         loop {
@@ -70,6 +107,17 @@ impl Hero {
         }
     }
 
+    pub fn from_kind(kind: HeroKind) -> Self {
+        match kind {
+            HeroKind::Warrior => Self::new_warrior(),
+            HeroKind::Mage => Self::new_mage(),
+            HeroKind::Archer => Self::new_archer(),
+            HeroKind::Engineer => Self::new_engineer(),
+            HeroKind::Assassin => Self::new_assassin(),
+            HeroKind::Priest => Self::new_priest(),
+        }
+    }
+
     pub fn new_warrior() -> Self {
         Self::Warrior { level: 0, exp: 0, energy: 0, act: false }
     }
@@ -371,7 +419,49 @@ impl Hero {
     }
 
     pub fn get_energy_left(&self) -> u8 {
-        self.get_rod_len() - self.get_energy()
+        self.get_rod_len().saturating_sub(self.get_energy())
+    }
+
+    /// Set the level, rejecting any above [`MAX_LEVEL`] or whose rod is too
+    /// short for the current energy.
+    pub fn set_level(&mut self, level: u8) -> Result<(), String> {
+        if level > MAX_LEVEL {
+            return Err(format!(
+                "level {} exceeds the maximum of {}", level, MAX_LEVEL));
+        }
+        let mut leveled = *self;
+        *leveled.get_level_mut() = level;
+        if self.get_energy() > leveled.get_rod_len() {
+            return Err(format!(
+                "energy {} exceeds the rod length of {} at level {}",
+                self.get_energy(), leveled.get_rod_len(), level));
+        }
+        *self = leveled;
+        Ok(())
+    }
+
+    /// Set the EXP, rejecting any that would already have caused a level up.
+    pub fn set_exp(&mut self, exp: u8) -> Result<(), String> {
+        if exp >= MAX_EXP {
+            return Err(format!("EXP {} must be less than {}", exp, MAX_EXP));
+        }
+        *self.get_exp_mut() = exp;
+        Ok(())
+    }
+
+    /// Set the energy, rejecting any beyond the rod length at the current
+    /// level.
+    pub fn set_energy(&mut self, energy: u8) -> Result<(), String> {
+        if self.get_level() > MAX_LEVEL {
+            return Err(format!("level {} exceeds the maximum of {}",
+                self.get_level(), MAX_LEVEL));
+        }
+        if energy > self.get_rod_len() {
+            return Err(format!("energy {} exceeds the rod length of {}",
+                energy, self.get_rod_len()));
+        }
+        *self.get_energy_mut() = energy;
+        Ok(())
     }
 
     pub fn level_inc(&mut self) -> bool {
@@ -380,21 +470,45 @@ impl Hero {
     }
 
     pub fn exp_inc(&mut self, inc: u8) -> bool {
+        let leveled_up: bool = self.exp_inc_carry(inc).is_some();
+        if leveled_up { *self.get_exp_mut() = 0; }
+        leveled_up
+    }
+
+    /// Like [`Self::exp_inc`], but EXP beyond what was needed to level up is
+    /// kept toward the next level. Returns the amount kept on a level up.
+    pub fn exp_inc_carry(&mut self, inc: u8) -> Option<u8> {
         let exp: &mut u8 = self.get_exp_mut();
         *exp += inc;
-        let leveled_up: bool = *exp >= MAX_EXP;
-        if leveled_up { *exp = 0; }
-        leveled_up
+        if *exp >= MAX_EXP {
+            *exp = (*exp - MAX_EXP).min(MAX_EXP - 1);
+            Some(*exp)
+        } else {
+            None
+        }
     }
 
     pub fn energy_inc(&mut self, inc: u8) -> bool {
+        let will_act: bool = self.energy_inc_carry(inc).is_some();
+        if will_act { *self.get_energy_mut() = 0; }
+        will_act
+    }
+
+    /// Like [`Self::energy_inc`], but energy beyond what was needed to fill
+    /// the rod is kept toward the next one. Returns the amount kept if the
+    /// hero will act.
+    pub fn energy_inc_carry(&mut self, inc: u8) -> Option<u8> {
         let rod_len: u8 = self.get_rod_len();
         let energy: &mut u8 = self.get_energy_mut();
         *energy += inc;
-        let will_act: bool = *energy >= rod_len;
-        if will_act { *energy = 0; }
-        if will_act { *self.get_act_mut() = true }
-        will_act
+        if *energy >= rod_len {
+            *energy = (*energy - rod_len).min(rod_len - 1);
+            let carry = *energy;
+            self.set_act(true);
+            Some(carry)
+        } else {
+            None
+        }
     }
 
     pub fn energy_dec(&mut self, dec: u8) {
@@ -404,9 +518,92 @@ impl Hero {
         if act && *energy == 0 {
             *energy = rod_len.saturating_sub(dec);
             self.set_act(false);
+        } else if act {
+            // energy carried past a full rod is lost first
+            let total = (rod_len + *energy).saturating_sub(dec);
+            if total >= rod_len {
+                *energy = total - rod_len;
+            } else {
+                *energy = total;
+                self.set_act(false);
+            }
         } else {
             *energy = energy.saturating_sub(dec);
         }
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pair_from_name() {
+        assert_eq!(
+            HeroKind::pair_from_name("warrior/ Mage"),
+            Ok((HeroKind::Warrior, HeroKind::Mage)),
+        );
+        assert_eq!(
+            HeroKind::pair_from_name("Warrior"),
+            Err("invalid loadout 'Warrior'".to_string()),
+        );
+        assert_eq!(
+            HeroKind::pair_from_name("Mage/Mage"),
+            Err("invalid loadout 'Mage/Mage'".to_string()),
+        );
+        assert_eq!(
+            HeroKind::pair_from_name("Mage/Bard"),
+            Err("unknown hero 'Bard'".to_string()),
+        );
+    }
+
+    #[test]
+    fn set_level() {
+        let mut mage = Hero::from_kind(HeroKind::Mage);
+        assert_eq!(mage.set_level(2), Ok(()));
+        assert_eq!(mage.get_level(), 2);
+        assert_eq!(mage.set_level(MAX_LEVEL + 1),
+            Err("level 3 exceeds the maximum of 2".to_string()));
+        assert_eq!(mage.get_level(), 2);
+
+        // a level 0 Mage's rod holds 5, but only 4 from level 1
+        let mut mage = Hero::from_kind(HeroKind::Mage);
+        mage.set_energy(5).unwrap();
+        assert_eq!(mage.set_level(1),
+            Err("energy 5 exceeds the rod length of 4 at level 1".to_string()));
+        assert_eq!(mage.get_level(), 0);
+        mage.set_energy(4).unwrap();
+        assert_eq!(mage.set_level(1), Ok(()));
+
+        let mut engineer = Hero::from_kind(HeroKind::Engineer);
+        engineer.set_energy(4).unwrap();
+        assert_eq!(engineer.set_level(1), Ok(()));
+        assert!(engineer.set_level(2).is_err());
+        assert_eq!(engineer.get_level(), 1);
+    }
+
+    #[test]
+    fn set_exp() {
+        let mut hero = Hero::from_kind(HeroKind::Warrior);
+        assert_eq!(hero.set_exp(MAX_EXP - 1), Ok(()));
+        assert_eq!(hero.get_exp(), MAX_EXP - 1);
+        assert_eq!(hero.set_exp(MAX_EXP),
+            Err(format!("EXP {} must be less than {}", MAX_EXP, MAX_EXP)));
+        assert_eq!(hero.get_exp(), MAX_EXP - 1);
+    }
+
+    #[test]
+    fn set_energy() {
+        let mut priest = Hero::from_kind(HeroKind::Priest);
+        assert_eq!(priest.set_energy(4), Ok(()));
+        priest.set_energy(0).unwrap();
+        priest.set_level(1).unwrap();
+        assert_eq!(priest.set_energy(4),
+            Err("energy 4 exceeds the rod length of 3".to_string()));
+        assert_eq!(priest.get_energy(), 0);
+
+        *priest.get_level_mut() = MAX_LEVEL + 1;
+        assert_eq!(priest.set_energy(0),
+            Err("level 3 exceeds the maximum of 2".to_string()));
+    }
+}
@@ -0,0 +1,57 @@
+//! A JSON view of the board, shared by the front ends that speak JSON.
+//!
+//! Players are numbered 1 and 2, and wheels are written as by their
+//! `Display`.
+
+use serde_json::{ Value, json };
+use crate::engine::{
+    game::{ PlayerPos, Winner },
+    hero::Hero,
+    player::{ Player, HeroPos },
+    wheel::{ Wheel, Rolls },
+};
+
+/// The number of the player at `pos`, 1 or 2.
+pub fn player_num(pos: PlayerPos) -> u8 {
+    match pos {
+        PlayerPos::P1 => 1,
+        PlayerPos::P2 => 2,
+    }
+}
+
+/// `{"kind", "level", "exp", "energy", "rodLen"}`.
+pub fn hero_json(hero: &Hero) -> Value {
+    json!({
+        "kind": hero.get_kind().to_string(),
+        "level": hero.get_level(),
+        "exp": hero.get_exp(),
+        "energy": hero.get_energy(),
+        "rodLen": hero.get_rod_len(),
+    })
+}
+
+/// `{"name", "crown", "bulwark", "heroes"}`, with the left hero first.
+pub fn player_json(player: &Player) -> Value {
+    json!({
+        "name": player.get_name(),
+        "crown": player.get_crown(),
+        "bulwark": player.get_bulwark(),
+        "heroes": [
+            hero_json(player.get_hero(HeroPos::L)),
+            hero_json(player.get_hero(HeroPos::R)),
+        ],
+    })
+}
+
+/// An array of five wheels, e.g. `["S", "DD*", "H", "S", "SS*"]`.
+pub fn rolls_json(rolls: &Rolls) -> Value {
+    rolls.iter().map(Wheel::to_string).collect()
+}
+
+/// `{"player", "reason"}`, where `player` is `null` for a draw.
+pub fn winner_json(winner: Winner) -> Value {
+    json!({
+        "player": winner.player().map(player_num),
+        "reason": winner.reason().to_string(),
+    })
+}
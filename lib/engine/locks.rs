@@ -0,0 +1,364 @@
+use crate::engine::wheel::{ Wheel, WheelKind, Rolls };
+
+/// An error in a line of lock commands, pointing at the offending token.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LockError {
+    /// Character offset of the token in the input.
+    pub start: usize,
+    /// Length of the token in characters.
+    pub len: usize,
+    pub msg: String,
+}
+
+impl LockError {
+    /// Render the error below a copy of `input`, with the offending token
+    /// underlined.
+    pub fn render(&self, input: &str) -> String {
+        format!("{}\n{}{} {}",
+            input.trim_end(),
+            " ".repeat(self.start),
+            "^".repeat(self.len.max(1)),
+            self.msg,
+        )
+    }
+}
+
+/// What to do with the wheels selected by a token.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Op {
+    Lock,
+    Unlock,
+    Toggle,
+}
+
+/// Wheels matched by a symbol such as `S`, `DD` or `D*`.
+///
+/// A single letter matches every wheel of that kind, while a repeated letter
+/// matches only that many symbols. A trailing `*` matches only wheels that
+/// count toward EXP, and may also be used alone.
+#[derive(Copy, Clone, Debug)]
+struct Symbol {
+    kind: Option<WheelKind>,
+    count: Option<u8>,
+    exp: bool,
+}
+
+impl Symbol {
+    fn parse(s: &str) -> Option<Self> {
+        let upper = s.to_ascii_uppercase();
+        let (letters, exp)
+            = match upper.strip_suffix('*') {
+                Some(letters) => (letters, true),
+                None => (upper.as_str(), false),
+            };
+        let Some(first) = letters.chars().next() else {
+            return exp.then_some(Self { kind: None, count: None, exp });
+        };
+        let n = letters.len();
+        if n > 3 || letters.chars().any(|c| c != first) { return None; }
+        let kind
+            = match (first, exp) {
+                ('S', _) => WheelKind::Square,
+                ('D', _) => WheelKind::Diamond,
+                ('H', false) => WheelKind::Hammer,
+                _ => { return None; },
+            };
+        let count = (n > 1).then_some(n as u8);
+        Some(Self { kind: Some(kind), count, exp })
+    }
+
+    fn matches(&self, wheel: &Wheel) -> bool {
+        let (n, exp)
+            = match *wheel {
+                Wheel::Square(n, b) | Wheel::Diamond(n, b) => (n, b),
+                Wheel::Hammer(n) => (n, false),
+            };
+        self.kind.is_none_or(|kind| kind == wheel.get_kind())
+            && self.count.is_none_or(|count| count == n)
+            && (!self.exp || exp)
+    }
+}
+
+/// Split `input` into tokens separated by whitespace or commas, along with
+/// their character offsets.
+fn tokenize(input: &str) -> Vec<(usize, &str)> {
+    let mut tokens: Vec<(usize, &str)> = Vec::new();
+    let mut start: Option<(usize, usize)> = None;
+    for (col, (k, c)) in input.char_indices().enumerate() {
+        let sep = c.is_whitespace() || c == ',';
+        match (start, sep) {
+            (None, false) => { start = Some((k, col)); },
+            (Some((s, s_col)), true) => {
+                tokens.push((s_col, &input[s..k]));
+                start = None;
+            },
+            _ => { },
+        }
+    }
+    if let Some((s, s_col)) = start {
+        tokens.push((s_col, &input[s..]));
+    }
+    tokens
+}
+
+/// Parse a wheel number or range of wheel numbers such as `2` or `1-3`.
+fn parse_wheels(s: &str) -> Option<Result<[bool; 5], String>> {
+    let (a, b) = s.split_once('-').unwrap_or((s, s));
+    let a: usize = a.parse().ok()?;
+    let b: usize = b.parse().ok()?;
+    if !(1..=5).contains(&a) || !(1..=5).contains(&b) {
+        return Some(Err("wheels are numbered 1-5".to_string()));
+    }
+    if a > b {
+        return Some(Err(
+            format!("range must be increasing, e.g. '{}-{}'", b, a)));
+    }
+    let mut sel = [false; 5];
+    sel[a - 1..b].iter_mut().for_each(|s| { *s = true; });
+    Some(Ok(sel))
+}
+
+/// Apply a line of lock commands to `locks`, leaving them unchanged on error.
+///
+/// Commands are separated by whitespace or commas and applied in order:
+/// - `2` or `1-3` toggles the lock on a wheel or range of wheels;
+/// - `all` or `none` locks or unlocks every wheel;
+/// - `S`, `DD`, `D*` etc. lock every wheel showing a matching symbol (see
+///   below);
+/// - any of the above prefixed with `+` or `-` locks or unlocks the selected
+///   wheels.
+///
+/// A symbol made of a single letter (`S`, `D` or `H`) matches every wheel of
+/// that kind, a repeated letter matches only wheels showing exactly that
+/// many, and a trailing `*` matches only wheels that count toward EXP.
+///
+/// If `permanent` is `true`, none of the wheels locked in `locks` may be
+/// unlocked.
+pub fn apply(
+    input: &str,
+    rolls: &Rolls,
+    locks: &mut [bool; 5],
+    permanent: bool,
+) -> Result<(), LockError>
+{
+    let mut new = *locks;
+    for (start, token) in tokenize(input) {
+        let err = |msg: String| {
+            LockError { start, len: token.chars().count(), msg }
+        };
+        let (prefix, body)
+            = if let Some(rest) = token.strip_prefix('+') {
+                (Some(Op::Lock), rest)
+            } else if let Some(rest) = token.strip_prefix('-') {
+                (Some(Op::Unlock), rest)
+            } else {
+                (None, token)
+            };
+        let (op, sel): (Op, [bool; 5])
+            = match (prefix, body.to_ascii_lowercase().as_str()) {
+                (_, "") => {
+                    return Err(err("expected a selection".to_string()));
+                },
+                (None, "all") => (Op::Lock, [true; 5]),
+                (None, "none") => (Op::Unlock, [true; 5]),
+                (Some(_), "all" | "none") => {
+                    return Err(err(format!("'{}' takes no prefix", body)));
+                },
+                (prefix, _) => {
+                    if let Some(res) = parse_wheels(body) {
+                        (prefix.unwrap_or(Op::Toggle), res.map_err(err)?)
+                    } else if let Some(symbol) = Symbol::parse(body) {
+                        let mut sel = [false; 5];
+                        sel.iter_mut().zip(rolls.iter())
+                            .for_each(|(s, w)| { *s = symbol.matches(w); });
+                        (prefix.unwrap_or(Op::Lock), sel)
+                    } else {
+                        return Err(err(
+                            format!("unrecognized selection '{}'", body)));
+                    }
+                },
+            };
+        for (k, lock) in new.iter_mut().enumerate() {
+            if !sel[k] { continue; }
+            let next
+                = match op {
+                    Op::Lock => true,
+                    Op::Unlock => false,
+                    Op::Toggle => !*lock,
+                };
+            if permanent && locks[k] && !next {
+                return Err(err(format!(
+                    "wheel {} is locked for the rest of the turn", k + 1)));
+            }
+            *lock = next;
+        }
+    }
+    *locks = new;
+    Ok(())
+}
+
+/// Render the shortest line of commands that [`apply`] turns from `from` into
+/// `to`, using `+` and `-` so that it works whatever is already locked, e.g.
+/// `+1-2 -4`. Empty if nothing changes.
+pub fn fmt_command(from: &[bool; 5], to: &[bool; 5]) -> String {
+    let mut cmds: Vec<String> = Vec::new();
+    let mut k = 0;
+    while k < 5 {
+        if from[k] == to[k] {
+            k += 1;
+            continue;
+        }
+        let start = k;
+        while k + 1 < 5 && from[k + 1] != to[k + 1] && to[k + 1] == to[start] {
+            k += 1;
+        }
+        let op = if to[start] { '+' } else { '-' };
+        cmds.push(
+            if start == k {
+                format!("{}{}", op, start + 1)
+            } else {
+                format!("{}{}-{}", op, start + 1, k + 1)
+            });
+        k += 1;
+    }
+    cmds.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::wheel::parse_rolls;
+    use super::*;
+
+    const F: bool = false;
+    const T: bool = true;
+
+    /// Apply `input` to `locks` over the rolls `S SS* D* DD HH`.
+    fn run(input: &str, locks: [bool; 5], permanent: bool)
+        -> Result<[bool; 5], LockError>
+    {
+        let rolls = parse_rolls("S SS* D* DD HH").unwrap();
+        let mut locks = locks;
+        apply(input, &rolls, &mut locks, permanent).map(|_| locks)
+    }
+
+    fn error(start: usize, len: usize, msg: &str) -> LockError {
+        LockError { start, len, msg: msg.to_string() }
+    }
+
+    #[test]
+    fn tokens() {
+        assert_eq!(tokenize(" 1, 2-3\tall,,x "),
+            vec![(1, "1"), (4, "2-3"), (8, "all"), (13, "x")]);
+        // offsets count characters rather than bytes
+        assert_eq!(tokenize("é,ü 2"), vec![(0, "é"), (2, "ü"), (4, "2")]);
+        assert!(tokenize(" ,, ").is_empty());
+    }
+
+    #[test]
+    fn symbols() {
+        let parse = |s: &str| {
+            Symbol::parse(s).map(|sym| (sym.kind, sym.count, sym.exp))
+        };
+        assert_eq!(parse("s"), Some((Some(WheelKind::Square), None, false)));
+        assert_eq!(parse("DD*"),
+            Some((Some(WheelKind::Diamond), Some(2), true)));
+        assert_eq!(parse("HHH"),
+            Some((Some(WheelKind::Hammer), Some(3), false)));
+        assert_eq!(parse("*"), Some((None, None, true)));
+        for bad in ["", "SSSS", "SD", "H*", "X", "**"] {
+            assert!(parse(bad).is_none(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn ranges() {
+        assert_eq!(run("2", [F; 5], false), Ok([F, T, F, F, F]));
+        assert_eq!(run("2 2", [F; 5], false), Ok([F; 5]));
+        assert_eq!(run("1-3", [T, F, F, F, F], false), Ok([F, T, T, F, F]));
+        assert_eq!(run("5-5, 4", [F; 5], false), Ok([F, F, F, T, T]));
+    }
+
+    #[test]
+    fn all_and_none() {
+        assert_eq!(run("all", [F, T, F, F, F], false), Ok([T; 5]));
+        assert_eq!(run("NONE", [T; 5], false), Ok([F; 5]));
+        assert_eq!(run("all 3", [F; 5], false), Ok([T, T, F, T, T]));
+    }
+
+    #[test]
+    fn prefixes() {
+        assert_eq!(run("+1-2", [T, F, F, F, F], false), Ok([T, T, F, F, F]));
+        assert_eq!(run("-1 -4", [T; 5], false), Ok([F, T, T, F, T]));
+        assert_eq!(run("+4,-4", [F; 5], false), Ok([F; 5]));
+        assert_eq!(run("-S", [T; 5], false), Ok([F, F, T, T, T]));
+        assert_eq!(run("+hh", [F; 5], false), Ok([F, F, F, F, T]));
+    }
+
+    #[test]
+    fn symbol_selectors() {
+        assert_eq!(run("S", [F; 5], false), Ok([T, T, F, F, F]));
+        assert_eq!(run("DD", [F; 5], false), Ok([F, F, F, T, F]));
+        assert_eq!(run("D*", [F; 5], false), Ok([F, F, T, F, F]));
+        assert_eq!(run("*", [F; 5], false), Ok([F, T, T, F, F]));
+        // symbols lock rather than toggle
+        assert_eq!(run("h", [F, F, F, F, T], false), Ok([F, F, F, F, T]));
+        assert_eq!(run("SSS", [F; 5], false), Ok([F; 5]));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(run("1 6", [F; 5], false),
+            Err(error(2, 1, "wheels are numbered 1-5")));
+        assert_eq!(run("0-2", [F; 5], false),
+            Err(error(0, 3, "wheels are numbered 1-5")));
+        assert_eq!(run("2, 3-1", [F; 5], false),
+            Err(error(3, 3, "range must be increasing, e.g. '1-3'")));
+        assert_eq!(run("all +", [F; 5], false),
+            Err(error(4, 1, "expected a selection")));
+        assert_eq!(run("+all", [F; 5], false),
+            Err(error(0, 4, "'all' takes no prefix")));
+        assert_eq!(run("1,  xyz", [F; 5], false),
+            Err(error(4, 3, "unrecognized selection 'xyz'")));
+        assert_eq!(run("-HHHH", [F; 5], false),
+            Err(error(0, 5, "unrecognized selection 'HHHH'")));
+    }
+
+    #[test]
+    fn errors_leave_locks_unchanged() {
+        let rolls = parse_rolls("S SS* D* DD HH").unwrap();
+        let mut locks = [F, T, F, F, F];
+        let e = apply("1 2 9", &rolls, &mut locks, false).unwrap_err();
+        assert_eq!(locks, [F, T, F, F, F]);
+        assert_eq!(e.render("1 2 9"), "1 2 9\n    ^ wheels are numbered 1-5");
+    }
+
+    #[test]
+    fn permanent_locks() {
+        let locked = [T, F, F, F, F];
+        let msg = "wheel 1 is locked for the rest of the turn";
+        assert_eq!(run("1", locked, true), Err(error(0, 1, msg)));
+        assert_eq!(run("2 none", locked, true), Err(error(2, 4, msg)));
+        assert_eq!(run("-S", locked, true), Err(error(0, 2, msg)));
+        assert_eq!(run("+1 2 all", locked, true), Ok([T; 5]));
+        // wheels locked earlier in the same line may still be unlocked
+        assert_eq!(run("2 2", locked, true), Ok(locked));
+    }
+
+    #[test]
+    fn commands() {
+        assert_eq!(fmt_command(&[F; 5], &[F; 5]), "");
+        assert_eq!(fmt_command(&[F; 5], &[T, T, F, T, F]), "+1-2 +4");
+        assert_eq!(fmt_command(&[T, F, T, T, F], &[T, T, F, F, T]),
+            "+2 -3-4 +5");
+        assert_eq!(fmt_command(&[T; 5], &[F; 5]), "-1-5");
+        // every command takes every set of locks to its target
+        for mask_from in 0..32_u8 {
+            for mask_to in 0..32_u8 {
+                let from = [0, 1, 2, 3, 4].map(|k| mask_from & (1 << k) != 0);
+                let to = [0, 1, 2, 3, 4].map(|k| mask_to & (1 << k) != 0);
+                let command = fmt_command(&from, &to);
+                assert_eq!(run(&command, from, false), Ok(to), "{}", command);
+            }
+        }
+    }
+}
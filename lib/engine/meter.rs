@@ -0,0 +1,165 @@
+use rand::{ Rng, SeedableRng, rngs::StdRng };
+use crate::engine::{
+    game::{ Game, PlayerPos, Winner },
+    solver::{ Solver, WinProbs },
+    strategy::Strategy,
+};
+
+/// Default number of games played out for each simulated estimate.
+pub const METER_SAMPLES: usize = 400;
+
+/// Default seed for the games played out by a [`WinMeter`].
+pub const METER_SEED: u64 = 0;
+
+/// Limit on the number of states explored for an exact estimate.
+const METER_STATES: usize = 2_000;
+
+/// Estimate the probability of each way `game` can end, assuming both players
+/// lock by [`Strategy::Classic`] from here on.
+///
+/// On the last turn before a turn limit the estimate is exact (see
+/// [`Solver`]); otherwise it is the outcome of `samples` games played out
+/// headless.
+pub fn estimate<R>(game: &Game, samples: usize, rng: &mut R) -> WinProbs
+where R: Rng + ?Sized
+{
+    let last_turn
+        = game.get_rules().max_turns
+        .is_some_and(|max| game.get_turn() + 1 >= max);
+    if last_turn {
+        let exact
+            = Solver::new(Strategy::Classic, Strategy::Classic)
+            .max_states(METER_STATES)
+            .solve(game);
+        if let Ok(probs) = exact { return probs; }
+    }
+    let mut probs = WinProbs::default();
+    if samples == 0 { return probs; }
+    let p = 1.0 / samples as f64;
+    for _ in 0..samples {
+        let mut g = game.clone();
+        match g.run_headless(Strategy::Classic, Strategy::Classic, rng).0 {
+            Winner::P1(_) => { probs.p1 += p; },
+            Winner::P2(_) => { probs.p2 += p; },
+            Winner::Draw(_) => { probs.draw += p; },
+        }
+    }
+    probs
+}
+
+/// Each player's estimated chance to win as a game goes on, and how much the
+/// last turn changed it.
+///
+/// Every estimate plays its games out from the same seed, so that the swing
+/// between two turns reflects the change in the game rather than sampling
+/// noise.
+#[derive(Clone, Debug)]
+pub struct WinMeter {
+    samples: usize,
+    seed: u64,
+    current: Option<WinProbs>,
+    previous: Option<WinProbs>,
+}
+
+impl Default for WinMeter {
+    fn default() -> Self { Self::new() }
+}
+
+impl WinMeter {
+    pub fn new() -> Self {
+        Self {
+            samples: METER_SAMPLES,
+            seed: METER_SEED,
+            current: None,
+            previous: None,
+        }
+    }
+
+    /// Set the number of games played out for each simulated estimate.
+    pub fn samples(mut self, samples: usize) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    /// Set the seed every estimate plays its games out from.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Estimate the chances of each player from `game`, keeping the last
+    /// estimate to compare against.
+    pub fn update(&mut self, game: &Game) {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        self.previous = self.current.take();
+        self.current = Some(estimate(game, self.samples, &mut rng));
+    }
+
+    /// The latest estimate, if any.
+    pub fn get(&self) -> Option<&WinProbs> { self.current.as_ref() }
+
+    /// Change in the chance of the player at `pos` to win since the previous
+    /// estimate, if there are two.
+    pub fn swing(&self, pos: PlayerPos) -> Option<f64> {
+        self.current.zip(self.previous)
+            .map(|(cur, prev)| cur.get(pos) - prev.get(pos))
+    }
+
+    /// The chance of the player at `pos` to win in percent, followed by the
+    /// swing in percentage points, e.g. `62% (+14)`.
+    pub fn label(&self, pos: PlayerPos) -> String {
+        let pct = |probs: WinProbs| (100.0 * probs.get(pos)).round() as i32;
+        match (self.current, self.previous) {
+            (Some(cur), Some(prev)) => {
+                // compare rounded values so that the numbers shown add up
+                format!("{}% ({:+})", pct(cur), pct(cur) - pct(prev))
+            },
+            (Some(cur), None) => format!("{}%", pct(cur)),
+            (None, _) => "?".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::{
+        builder::GameBuilder,
+        hero::HeroKind,
+    };
+    use super::*;
+
+    fn probs(p1: f64, p2: f64) -> WinProbs {
+        WinProbs { p1, p2, draw: 1.0 - p1 - p2 }
+    }
+
+    #[test]
+    fn label() {
+        let mut meter = WinMeter::new();
+        assert_eq!(meter.label(PlayerPos::P1), "?");
+        meter.current = Some(probs(0.616, 0.384));
+        assert_eq!(meter.label(PlayerPos::P1), "62%");
+        assert_eq!(meter.label(PlayerPos::P2), "38%");
+        meter.previous = Some(probs(0.48, 0.52));
+        assert_eq!(meter.label(PlayerPos::P1), "62% (+14)");
+        assert_eq!(meter.label(PlayerPos::P2), "38% (-14)");
+        meter.previous = meter.current;
+        assert_eq!(meter.label(PlayerPos::P1), "62% (+0)");
+    }
+
+    #[test]
+    fn unchanged_game_does_not_swing() {
+        let game
+            = GameBuilder::new(
+                (HeroKind::Warrior, HeroKind::Mage),
+                (HeroKind::Archer, HeroKind::Priest),
+            )
+            .build()
+            .unwrap();
+        let mut meter = WinMeter::new().samples(50);
+        meter.update(&game);
+        assert_eq!(meter.swing(PlayerPos::P1), None);
+        meter.update(&game);
+        assert_eq!(meter.swing(PlayerPos::P1), Some(0.0));
+        assert_eq!(meter.swing(PlayerPos::P2), Some(0.0));
+    }
+}
@@ -1,7 +1,29 @@
 pub mod wheel;
 pub mod hero;
 pub mod player;
+pub mod handicap;
 pub mod game;
+pub mod rules;
+pub mod draft;
+pub mod locks;
+pub mod builder;
+pub mod validate;
+pub mod advisor;
+pub mod balance;
+pub mod strategy;
+pub mod tournament;
+pub mod batch;
+pub mod series;
+pub mod stats;
+pub mod solver;
+pub mod packed;
+pub mod meter;
+pub mod profile;
+pub mod replay;
+pub mod blunder;
+pub mod fairness;
+#[cfg(feature = "json")]
+pub mod json;
 
 /// Call `print!` and automatically flush.
 #[macro_export]
@@ -0,0 +1,260 @@
+use std::{
+    collections::HashMap,
+    hash::{ BuildHasherDefault, Hash, Hasher },
+};
+use crate::engine::{
+    game::{ Game, PlayerPos },
+    handicap::Handicap,
+    hero::{ Hero, HeroKind },
+    player::{ Player, HeroPos },
+    rules::{ Rules, TurnMode },
+};
+
+/// Bits per hero: kind, level, EXP, energy and act flag.
+const HERO_BITS: [u32; 5] = [3, 2, 3, 3, 1];
+
+/// Bits per player: Crown, Bulwark and extra re-spins, then both heroes.
+const PLAYER_BITS: [u32; 3] = [4, 3, 4];
+
+/// Bits for the turn number, and for turn limits stored as one more than
+/// their value, so that 0 means none.
+const TURN_BITS: u32 = 16;
+
+/// Writes fields into a `u128` from the least significant bit up.
+struct Packer {
+    bits: u128,
+    at: u32,
+}
+
+impl Packer {
+    fn put<T>(&mut self, value: T, width: u32, what: &str)
+        -> Result<(), String>
+    where T: Into<u128>
+    {
+        let value: u128 = value.into();
+        if value >> width != 0 {
+            return Err(format!("{} {} does not fit in {} bits",
+                what, value, width));
+        }
+        self.bits |= value << self.at;
+        self.at += width;
+        Ok(())
+    }
+}
+
+/// Reads fields written by a [`Packer`].
+struct Unpacker {
+    bits: u128,
+}
+
+impl Unpacker {
+    fn take(&mut self, width: u32) -> u8 {
+        let value = self.bits & ((1 << width) - 1);
+        self.bits >>= width;
+        value as u8
+    }
+
+    fn take_wide(&mut self, width: u32) -> usize {
+        let value = self.bits & ((1 << width) - 1);
+        self.bits >>= width;
+        value as usize
+    }
+}
+
+fn kind_index(kind: HeroKind) -> u8 {
+    HeroKind::all().into_iter()
+        .position(|k| k == kind)
+        .expect("every hero kind should be listed") as u8
+}
+
+fn pack_hero(packer: &mut Packer, hero: &Hero) -> Result<(), String> {
+    let [kind, level, exp, energy, act] = HERO_BITS;
+    packer.put(kind_index(hero.get_kind()), kind, "hero kind")?;
+    packer.put(hero.get_level(), level, "level")?;
+    packer.put(hero.get_exp(), exp, "EXP")?;
+    packer.put(hero.get_energy(), energy, "energy")?;
+    packer.put(hero.get_act(), act, "act flag")?;
+    Ok(())
+}
+
+fn unpack_hero(unpacker: &mut Unpacker) -> Result<Hero, String> {
+    let [kind, level, exp, energy, act] = HERO_BITS;
+    let kind = unpacker.take(kind) as usize;
+    let kind
+        = *HeroKind::all().get(kind)
+        .ok_or_else(|| format!("invalid hero kind {}", kind))?;
+    let mut hero = Hero::from_kind(kind);
+    hero.set_level(unpacker.take(level))?;
+    hero.set_exp(unpacker.take(exp))?;
+    hero.set_energy(unpacker.take(energy))?;
+    hero.set_act(unpacker.take(act) != 0);
+    Ok(hero)
+}
+
+fn pack_player(packer: &mut Packer, player: &Player) -> Result<(), String> {
+    let [crown, bulwark, spins] = PLAYER_BITS;
+    packer.put(player.get_crown(), crown, "crown")?;
+    packer.put(player.get_bulwark(), bulwark, "bulwark")?;
+    packer.put(player.get_handicap().spins, spins, "extra re-spins")?;
+    pack_hero(packer, player.get_hero(HeroPos::L))?;
+    pack_hero(packer, player.get_hero(HeroPos::R))?;
+    Ok(())
+}
+
+fn unpack_player(unpacker: &mut Unpacker, name: &str)
+    -> Result<Player, String>
+{
+    let [crown, bulwark, spins] = PLAYER_BITS;
+    let crown = unpacker.take(crown);
+    let bulwark = unpacker.take(bulwark);
+    let handicap
+        = Handicap { spins: unpacker.take(spins), ..Handicap::default() };
+    let hero_l = unpack_hero(unpacker)?;
+    let hero_r = unpack_hero(unpacker)?;
+    let mut player
+        = Player::new_handicap(
+            name,
+            Hero::from_kind(hero_l.get_kind()),
+            Hero::from_kind(hero_r.get_kind()),
+            handicap,
+        )?;
+    player.set_crown(crown)?;
+    player.set_bulwark(bulwark)?;
+    *player.get_hero_mut(HeroPos::L) = hero_l;
+    *player.get_hero_mut(HeroPos::R) = hero_r;
+    Ok(player)
+}
+
+/// A turn limit as stored: one more than its value, or 0 for none.
+fn limit_bits(limit: Option<usize>, what: &str) -> Result<u128, String> {
+    match limit {
+        None => Ok(0),
+        Some(n) if n < (1 << TURN_BITS) - 1 => Ok(n as u128 + 1),
+        Some(n) => Err(format!("{} {} is too large to pack", what, n)),
+    }
+}
+
+/// Everything about a [`Game`] that can affect how it plays out, packed into
+/// a single `u128`: the rules, the turn number, both players' Crown, Bulwark
+/// and re-spins, and each hero's kind, level, EXP, energy and act flag.
+///
+/// Player names, handicaps other than re-spins, and any draft, replay or
+/// statistics are not kept.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PackedState(u128);
+
+impl PackedState {
+    /// Pack the state of `game`, failing if a turn number or limit is too
+    /// large to fit.
+    pub fn new(game: &Game) -> Result<Self, String> {
+        let mut packer = Packer { bits: 0, at: 0 };
+        pack_player(&mut packer, game.get_player(PlayerPos::P1))?;
+        pack_player(&mut packer, game.get_player(PlayerPos::P2))?;
+        let rules = game.get_rules();
+        packer.put(game.get_turn() as u128, TURN_BITS, "turn")?;
+        packer.put(rules.turn_mode == TurnMode::Alternating, 1, "turn mode")?;
+        packer.put(rules.permanent_locks, 1, "permanent locks")?;
+        packer.put(rules.carry_over, 1, "carry-over")?;
+        packer.put(
+            limit_bits(rules.max_turns, "turn limit")?,
+            TURN_BITS,
+            "turn limit",
+        )?;
+        packer.put(
+            limit_bits(rules.sudden_death, "sudden death")?,
+            TURN_BITS,
+            "sudden death",
+        )?;
+        Ok(Self(packer.bits))
+    }
+
+    /// Check that `bits` describe a valid state, as returned by
+    /// [`Self::to_bits`].
+    pub fn from_bits(bits: u128) -> Result<Self, String> {
+        let packed = Self(bits);
+        let game = packed.unpack()?;
+        // unused bits must be clear, so that every state has one encoding
+        if Self::new(&game)? != packed {
+            return Err(format!("invalid packed state {:#x}", bits));
+        }
+        Ok(packed)
+    }
+
+    pub fn to_bits(self) -> u128 { self.0 }
+
+    /// Unpack into a new game, with players named "Player 1" and "Player 2".
+    pub fn to_game(self) -> Game {
+        self.unpack().expect("packed state should be valid")
+    }
+
+    fn unpack(self) -> Result<Game, String> {
+        let mut unpacker = Unpacker { bits: self.0 };
+        let p1 = unpack_player(&mut unpacker, "Player 1")?;
+        let p2 = unpack_player(&mut unpacker, "Player 2")?;
+        let turn = unpacker.take_wide(TURN_BITS);
+        let turn_mode
+            = if unpacker.take(1) != 0 {
+                TurnMode::Alternating
+            } else {
+                TurnMode::BothRoll
+            };
+        let permanent_locks = unpacker.take(1) != 0;
+        let carry_over = unpacker.take(1) != 0;
+        let max_turns = unpacker.take_wide(TURN_BITS).checked_sub(1);
+        let sudden_death = unpacker.take_wide(TURN_BITS).checked_sub(1);
+        let mut game = Game::new(p1, p2);
+        game.set_rules(Rules {
+            turn_mode,
+            permanent_locks,
+            carry_over,
+            max_turns,
+            sudden_death,
+        });
+        game.set_turn(turn);
+        Ok(game)
+    }
+}
+
+/// Hashes the `u128` of a [`PackedState`] with a couple of multiplications,
+/// instead of the much slower default hasher.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct PackedHasher(u64);
+
+impl PackedHasher {
+    const K: u64 = 0x9e37_79b9_7f4a_7c15;
+
+    /// Add `x` to the hash, with the finalizer of SplitMix64.
+    fn mix(&mut self, x: u64) {
+        let mut z = (self.0 ^ x).wrapping_add(Self::K);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        self.0 = z ^ (z >> 31);
+    }
+}
+
+impl Hasher for PackedHasher {
+    fn finish(&self) -> u64 { self.0 }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for chunk in bytes.chunks(8) {
+            let mut word = [0; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            self.mix(u64::from_le_bytes(word));
+        }
+    }
+
+    fn write_u64(&mut self, n: u64) { self.mix(n); }
+
+    fn write_u128(&mut self, n: u128) {
+        self.mix(n as u64);
+        self.mix((n >> 64) as u64);
+    }
+}
+
+impl Hash for PackedState {
+    fn hash<H: Hasher>(&self, state: &mut H) { state.write_u128(self.0); }
+}
+
+/// A [`HashMap`] keyed by [`PackedState`]s, hashed with a [`PackedHasher`].
+pub type PackedMap<V>
+    = HashMap<PackedState, V, BuildHasherDefault<PackedHasher>>;
@@ -1,9 +1,18 @@
-use std::{ cmp::Ordering, io };
-use rand::prelude::{ Rng, thread_rng, };
+use std::cmp::Ordering;
+#[cfg(feature = "terminal")]
+use std::io;
+use rand::Rng;
+#[cfg(feature = "terminal")]
+use rand::prelude::thread_rng;
+#[cfg(feature = "terminal")]
 use crate::{
     print_flush,
     println_flush,
-    engine::hero::{ Hero, HeroKind },
+    engine::profile::Profiles,
+};
+use crate::engine::{
+    hero::{ Hero, HeroKind },
+    handicap::Handicap,
 };
 
 pub const INIT_CROWN: u8 = 10;
@@ -18,9 +27,10 @@ pub struct Player {
     bulwark: u8,
     hero_l: Hero,
     hero_r: Hero,
+    handicap: Handicap,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum HeroPos { L, R }
 
 impl HeroPos {
@@ -33,21 +43,54 @@ impl HeroPos {
 }
 
 impl Player {
-    pub(crate) fn get_choose() -> Self {
+    /// Choose a name, or pick one of the saved [`Profiles`] by number.
+    #[cfg(feature = "terminal")]
+    pub(crate) fn get_choose_name() -> String {
         let stdin = io::stdin();
-        println_flush!("Choose your name: ");
-        let name
-            = loop {
-                let mut name = String::new();
-                print_flush!(">>> ");
-                match stdin.read_line(&mut name) {
-                    Ok(_) => { break name.trim().to_string(); },
-                    Err(e) => {
-                        println_flush!("error reading input: {}", e);
-                        continue;
-                    },
-                }
-            };
+        let profiles
+            = Profiles::load_default()
+            .map(|profiles| profiles.get_profiles().to_vec())
+            .unwrap_or_else(|e| {
+                println_flush!("error loading profiles: {}", e);
+                Vec::new()
+            });
+        if profiles.is_empty() {
+            println_flush!("Choose your name: ");
+        } else {
+            println_flush!("Choose a profile, or type a new name:");
+            for (k, profile) in profiles.iter().enumerate() {
+                println_flush!("[{}] {}", k + 1, profile.summary());
+            }
+        }
+        loop {
+            let mut name = String::new();
+            print_flush!(">>> ");
+            match stdin.read_line(&mut name) {
+                Ok(_) => {
+                    let name = name.trim();
+                    break match name.parse::<usize>() {
+                        Ok(k) if (1..=profiles.len()).contains(&k)
+                            => profiles[k - 1].get_name().to_string(),
+                        _ => name.to_string(),
+                    };
+                },
+                Err(e) => {
+                    println_flush!("error reading input: {}", e);
+                    continue;
+                },
+            }
+        }
+    }
+
+    #[cfg(feature = "terminal")]
+    pub(crate) fn get_choose() -> Self {
+        let name = Self::get_choose_name();
+        Self::get_choose_heroes(&name)
+    }
+
+    /// Choose heroes for a player named `name`.
+    #[cfg(feature = "terminal")]
+    pub fn get_choose_heroes(name: &str) -> Self {
         println_flush!(
             "Choose your heroes:\n\
             [1] Warrior\n\
@@ -70,10 +113,11 @@ impl Player {
                     break hero_r;
                 }
             };
-        Self::new(&name, hero_l, hero_r)
+        Self::new(name, hero_l, hero_r)
     }
 
-    pub(crate) fn get_choose_cpu() -> Self {
+    #[cfg(feature = "terminal")]
+    pub fn get_choose_cpu() -> Self {
         let mut rng = thread_rng();
         let hero_l
             = match rng.gen_range(0..6) {
@@ -134,13 +178,69 @@ impl Player {
             bulwark: INIT_BULWARK,
             hero_l,
             hero_r,
+            handicap: Handicap::default(),
         }
     }
 
+    /// Like [`Self::new`], but starting from the position given by
+    /// `handicap`.
+    pub fn new_handicap(
+        name: &str,
+        mut hero_l: Hero,
+        mut hero_r: Hero,
+        handicap: Handicap,
+    ) -> Result<Self, String>
+    {
+        handicap.check()?;
+        for hero in [&mut hero_l, &mut hero_r] {
+            hero.set_level(handicap.level)?;
+            hero.set_exp(handicap.exp)?;
+        }
+        Ok(Self {
+            name: name.to_string(),
+            crown: handicap.crown,
+            bulwark: handicap.bulwark,
+            hero_l,
+            hero_r,
+            handicap,
+        })
+    }
+
+    pub fn get_handicap(&self) -> &Handicap { &self.handicap }
+
+    /// This player as they were at the start of the game, with the same
+    /// name, heroes and handicap.
+    pub fn restart(&self) -> Self {
+        let hero_l = Hero::from_kind(self.hero_l.get_kind());
+        let hero_r = Hero::from_kind(self.hero_r.get_kind());
+        Self::new_handicap(&self.name, hero_l, hero_r, self.handicap)
+            .expect("handicap should already be valid")
+    }
+
     pub fn get_crown(&self) -> u8 { self.crown }
 
+    /// Set the crown, rejecting any above [`MAX_CROWN`].
+    pub fn set_crown(&mut self, crown: u8) -> Result<(), String> {
+        if crown > MAX_CROWN {
+            return Err(format!(
+                "crown {} exceeds the maximum of {}", crown, MAX_CROWN));
+        }
+        self.crown = crown;
+        Ok(())
+    }
+
     pub fn get_bulwark(&self) -> u8 { self.bulwark }
 
+    /// Set the bulwark, rejecting any above [`MAX_BULWARK`].
+    pub fn set_bulwark(&mut self, bulwark: u8) -> Result<(), String> {
+        if bulwark > MAX_BULWARK {
+            return Err(format!(
+                "bulwark {} exceeds the maximum of {}", bulwark, MAX_BULWARK));
+        }
+        self.bulwark = bulwark;
+        Ok(())
+    }
+
     pub fn crown_inc(&mut self, inc: u8) {
         self.crown = self.crown.saturating_add(inc).min(MAX_CROWN);
     }
@@ -251,7 +351,9 @@ impl Player {
         }
     }
 
-    pub fn get_assassin_target(&self) -> (&Hero, HeroPos) {
+    pub fn get_assassin_target<R>(&self, rng: &mut R) -> (&Hero, HeroPos)
+    where R: Rng + ?Sized
+    {
         let energy_l: u8 = self.hero_l.get_energy();
         let kind_l: HeroKind = self.hero_l.get_kind();
         let act_l: bool = self.hero_l.get_act();
@@ -268,7 +370,6 @@ impl Player {
                     Ordering::Greater => (&self.hero_l, HeroPos::L),
                     Ordering::Less => (&self.hero_r, HeroPos::R),
                     Ordering::Equal => {
-                        let mut rng = thread_rng();
                         if rng.gen::<bool>() {
                             (&self.hero_l, HeroPos::L)
                         } else {
@@ -280,7 +381,10 @@ impl Player {
         }
     }
 
-    pub fn get_assassin_target_mut(&mut self) -> (&mut Hero, HeroPos) {
+    pub fn get_assassin_target_mut<R>(&mut self, rng: &mut R)
+        -> (&mut Hero, HeroPos)
+    where R: Rng + ?Sized
+    {
         let energy_l: u8 = self.hero_l.get_energy();
         let kind_l: HeroKind = self.hero_l.get_kind();
         let act_l: bool = self.hero_l.get_act();
@@ -297,7 +401,6 @@ impl Player {
                     Ordering::Greater => (&mut self.hero_l, HeroPos::L),
                     Ordering::Less => (&mut self.hero_r, HeroPos::R),
                     Ordering::Equal => {
-                        let mut rng = thread_rng();
                         if rng.gen::<bool>() {
                             (&mut self.hero_l, HeroPos::L)
                         } else {
@@ -310,3 +413,36 @@ impl Player {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player() -> Player {
+        Player::new(
+            "",
+            Hero::from_kind(HeroKind::Warrior),
+            Hero::from_kind(HeroKind::Mage),
+        )
+    }
+
+    #[test]
+    fn set_crown() {
+        let mut player = player();
+        assert_eq!(player.set_crown(MAX_CROWN), Ok(()));
+        assert_eq!(player.set_crown(0), Ok(()));
+        assert_eq!(player.set_crown(MAX_CROWN + 1),
+            Err(format!("crown {} exceeds the maximum of {}",
+                MAX_CROWN + 1, MAX_CROWN)));
+        assert_eq!(player.get_crown(), 0);
+    }
+
+    #[test]
+    fn set_bulwark() {
+        let mut player = player();
+        assert_eq!(player.set_bulwark(MAX_BULWARK), Ok(()));
+        assert_eq!(player.set_bulwark(MAX_BULWARK + 1),
+            Err(format!("bulwark {} exceeds the maximum of {}",
+                MAX_BULWARK + 1, MAX_BULWARK)));
+        assert_eq!(player.get_bulwark(), MAX_BULWARK);
+    }
+}
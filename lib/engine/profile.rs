@@ -0,0 +1,427 @@
+use std::{ env, fs, path::{ Path, PathBuf } };
+use crate::engine::{
+    game::{ PlayerPos, Winner },
+    hero::HeroKind,
+    replay::parse_field,
+};
+
+/// First line of every profiles file.
+pub const PROFILES_HEADER: &str = "wheels-profiles";
+
+/// Name of the file holding every profile in the [`data_dir`].
+pub const PROFILES_FILE: &str = "profiles.txt";
+
+/// Directory where local data is kept: `$WHEELS_DATA_DIR` if set, otherwise
+/// `$XDG_DATA_HOME/wheels`, falling back to `~/.local/share/wheels`.
+pub fn data_dir() -> Option<PathBuf> {
+    let var = |key: &str| env::var_os(key).filter(|v| !v.is_empty());
+    if let Some(dir) = var("WHEELS_DATA_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    var("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| {
+            var("HOME").map(|home| Path::new(&home).join(".local/share"))
+        })
+        .map(|dir| dir.join("wheels"))
+}
+
+/// How a game ended for one player.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Win,
+    Loss,
+    Draw,
+}
+
+impl Outcome {
+    /// The outcome of a game ending in `winner` for the player at `pos`.
+    pub fn of(winner: Winner, pos: PlayerPos) -> Self {
+        match winner.player() {
+            Some(p) if p == pos => Self::Win,
+            Some(_) => Self::Loss,
+            None => Self::Draw,
+        }
+    }
+}
+
+/// Wins, losses and draws.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Record {
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+}
+
+impl Record {
+    fn add(&mut self, outcome: Outcome) {
+        match outcome {
+            Outcome::Win => { self.wins += 1; },
+            Outcome::Loss => { self.losses += 1; },
+            Outcome::Draw => { self.draws += 1; },
+        }
+    }
+
+    pub fn games(&self) -> u32 { self.wins + self.losses + self.draws }
+
+    /// Fraction of games won, if any were played.
+    pub fn win_rate(&self) -> Option<f64> {
+        let games = self.games();
+        (games > 0).then(|| self.wins as f64 / games as f64)
+    }
+
+    fn parse(tokens: &[&str]) -> Result<Self, String> {
+        match tokens {
+            [wins, losses, draws] => Ok(Self {
+                wins: parse_field(wins, "wins")?,
+                losses: parse_field(losses, "losses")?,
+                draws: parse_field(draws, "draws")?,
+            }),
+            _ => Err("expected 'wins=N losses=N draws=N'".to_string()),
+        }
+    }
+}
+
+/// Written as `wins=N losses=N draws=N`.
+impl std::fmt::Display for Record {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "wins={} losses={} draws={}",
+            self.wins, self.losses, self.draws)
+    }
+}
+
+fn kind_index(kind: HeroKind) -> usize {
+    HeroKind::all().into_iter().position(|k| k == kind).unwrap()
+}
+
+/// Put a pair of heroes in menu order, so that either seating counts as the
+/// same pair.
+fn sort_pair(heroes: (HeroKind, HeroKind)) -> (HeroKind, HeroKind) {
+    let (a, b) = heroes;
+    if kind_index(a) <= kind_index(b) { (a, b) } else { (b, a) }
+}
+
+/// Lifetime results for a named player.
+#[derive(Clone, Debug)]
+pub struct Profile {
+    name: String,
+    record: Record,
+    /// Results with each hero, in the order of [`HeroKind::all`].
+    heroes: [Record; 6],
+    /// Number of games played with each pair of heroes.
+    pairs: Vec<((HeroKind, HeroKind), u32)>,
+    /// Current run of consecutive wins.
+    streak: u32,
+    best_streak: u32,
+}
+
+impl Profile {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            record: Record::default(),
+            heroes: [Record::default(); 6],
+            pairs: Vec::new(),
+            streak: 0,
+            best_streak: 0,
+        }
+    }
+
+    pub fn get_name(&self) -> &str { &self.name }
+
+    pub fn get_record(&self) -> Record { self.record }
+
+    pub fn get_hero_record(&self, kind: HeroKind) -> Record {
+        self.heroes[kind_index(kind)]
+    }
+
+    pub fn get_streak(&self) -> u32 { self.streak }
+
+    pub fn get_best_streak(&self) -> u32 { self.best_streak }
+
+    /// Every pair of heroes played, most played first.
+    pub fn favorite_pairs(&self) -> Vec<((HeroKind, HeroKind), u32)> {
+        let mut pairs = self.pairs.clone();
+        pairs.sort_by(|(pair_a, n_a), (pair_b, n_b)| {
+            n_b.cmp(n_a)
+                .then_with(|| {
+                    (kind_index(pair_a.0), kind_index(pair_a.1))
+                        .cmp(&(kind_index(pair_b.0), kind_index(pair_b.1)))
+                })
+        });
+        pairs
+    }
+
+    /// Add a game played with `heroes` that ended in `outcome`.
+    pub fn record(&mut self, heroes: (HeroKind, HeroKind), outcome: Outcome) {
+        self.record.add(outcome);
+        self.heroes[kind_index(heroes.0)].add(outcome);
+        self.heroes[kind_index(heroes.1)].add(outcome);
+        let pair = sort_pair(heroes);
+        match self.pairs.iter_mut().find(|(p, _)| *p == pair) {
+            Some((_, n)) => { *n += 1; },
+            None => { self.pairs.push((pair, 1)); },
+        }
+        if outcome == Outcome::Win {
+            self.streak += 1;
+            self.best_streak = self.best_streak.max(self.streak);
+        } else {
+            self.streak = 0;
+        }
+    }
+
+    /// A one-line summary, e.g. `Alice (5-3-1, 56% won, best streak 3)`.
+    pub fn summary(&self) -> String {
+        let rate
+            = self.record.win_rate()
+            .map(|r| format!(", {:.0}% won", 100.0 * r))
+            .unwrap_or_default();
+        format!("{} ({}-{}-{}{}, best streak {})",
+            self.name, self.record.wins, self.record.losses,
+            self.record.draws, rate, self.best_streak)
+    }
+
+    fn write_lines(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "profile {}", self.name)?;
+        writeln!(f, "record {}", self.record)?;
+        writeln!(f, "streak current={} best={}",
+            self.streak, self.best_streak)?;
+        for (kind, record) in HeroKind::all().into_iter().zip(&self.heroes) {
+            if record.games() > 0 {
+                writeln!(f, "hero {} {}", kind, record)?;
+            }
+        }
+        for ((a, b), n) in self.pairs.iter() {
+            writeln!(f, "pair {} {} games={}", a, b, n)?;
+        }
+        Ok(())
+    }
+}
+
+/// Multi-line report of lifetime results.
+impl std::fmt::Display for Profile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", self.summary())?;
+        writeln!(f, "  current streak {}", self.streak)?;
+        let heroes: Vec<String>
+            = HeroKind::all().into_iter()
+            .filter_map(|kind| {
+                let record = self.get_hero_record(kind);
+                record.win_rate()
+                    .map(|r| {
+                        format!("{} {:.0}% of {}",
+                            kind, 100.0 * r, record.games())
+                    })
+            })
+            .collect();
+        if !heroes.is_empty() {
+            writeln!(f, "  win rate by hero: {}", heroes.join(", "))?;
+        }
+        let pairs: Vec<String>
+            = self.favorite_pairs().into_iter()
+            .take(3)
+            .map(|((a, b), n)| format!("{}+{} ({})", a, b, n))
+            .collect();
+        if !pairs.is_empty() {
+            writeln!(f, "  favorite pairs: {}", pairs.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Every profile kept in a single file.
+#[derive(Clone, Debug)]
+pub struct Profiles {
+    path: PathBuf,
+    profiles: Vec<Profile>,
+}
+
+impl Profiles {
+    /// Read from a file at `path`, which need not exist yet.
+    pub fn load<P>(path: P) -> Result<Self, String>
+    where P: AsRef<Path>
+    {
+        let path = path.as_ref().to_path_buf();
+        let profiles
+            = match fs::read_to_string(&path) {
+                Ok(s) => parse_profiles(&s)
+                    .map_err(|e| format!("{}: {}", path.display(), e))?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound
+                    => Vec::new(),
+                Err(e) => {
+                    return Err(
+                        format!("error reading '{}': {}", path.display(), e));
+                },
+            };
+        Ok(Self { path, profiles })
+    }
+
+    /// Read from the profiles file in the [`data_dir`].
+    pub fn load_default() -> Result<Self, String> {
+        let dir
+            = data_dir()
+            .ok_or_else(|| "could not find a data directory".to_string())?;
+        Self::load(dir.join(PROFILES_FILE))
+    }
+
+    /// Write back to the file this was read from, creating its directory if
+    /// needed.
+    pub fn save(&self) -> Result<(), String> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| {
+                    format!("error creating '{}': {}", dir.display(), e)
+                })?;
+        }
+        fs::write(&self.path, self.to_string())
+            .map_err(|e| {
+                format!("error writing '{}': {}", self.path.display(), e)
+            })
+    }
+
+    pub fn get_path(&self) -> &Path { &self.path }
+
+    pub fn get_profiles(&self) -> &[Profile] { &self.profiles }
+
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|profile| profile.name == name)
+    }
+
+    /// Add a game to the profile named `name`, creating it if needed.
+    pub fn record(
+        &mut self,
+        name: &str,
+        heroes: (HeroKind, HeroKind),
+        outcome: Outcome,
+    ) {
+        let k
+            = match self.profiles.iter().position(|p| p.name == name) {
+                Some(k) => k,
+                None => {
+                    self.profiles.push(Profile::new(name));
+                    self.profiles.len() - 1
+                },
+            };
+        self.profiles[k].record(heroes, outcome);
+    }
+}
+
+impl std::fmt::Display for Profiles {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", PROFILES_HEADER)?;
+        for profile in self.profiles.iter() {
+            profile.write_lines(f)?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_hero(s: &str) -> Result<HeroKind, String> {
+    HeroKind::from_name(s).ok_or_else(|| format!("unknown hero '{}'", s))
+}
+
+fn parse_profiles(s: &str) -> Result<Vec<Profile>, String> {
+    let mut lines = s.lines().enumerate();
+    match lines.next() {
+        Some((_, PROFILES_HEADER)) => { },
+        _ => { return Err("missing profiles header".to_string()); },
+    }
+    let mut profiles: Vec<Profile> = Vec::new();
+    for (n, line) in lines {
+        let err = |e: String| format!("line {}: {}", n + 1, e);
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if let ["profile", ..] = tokens.as_slice() {
+            let name = line.split_once(' ').map(|(_, name)| name.trim());
+            match name {
+                Some(name) if !name.is_empty() => {
+                    profiles.push(Profile::new(name));
+                },
+                _ => { return Err(err("missing profile name".to_string())); },
+            }
+            continue;
+        }
+        let Some(profile) = profiles.last_mut() else {
+            if tokens.is_empty() { continue; }
+            return Err(err("expected 'profile' first".to_string()));
+        };
+        match tokens.as_slice() {
+            [] => { },
+            ["record", rest @ ..] => {
+                profile.record = Record::parse(rest).map_err(err)?;
+            },
+            ["streak", current, best] => {
+                profile.streak
+                    = parse_field(current, "current").map_err(err)?;
+                profile.best_streak
+                    = parse_field(best, "best").map_err(err)?;
+            },
+            ["hero", kind, rest @ ..] => {
+                let kind = parse_hero(kind).map_err(err)?;
+                profile.heroes[kind_index(kind)]
+                    = Record::parse(rest).map_err(err)?;
+            },
+            ["pair", a, b, games] => {
+                let pair
+                    = sort_pair((
+                        parse_hero(a).map_err(err)?,
+                        parse_hero(b).map_err(err)?,
+                    ));
+                let games = parse_field(games, "games").map_err(err)?;
+                profile.pairs.push((pair, games));
+            },
+            _ => { return Err(err(format!("unrecognized line '{}'", line))); },
+        }
+    }
+    Ok(profiles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profiles_round_trip() {
+        let mut profiles = Profiles {
+            path: PathBuf::from("profiles.txt"),
+            profiles: Vec::new(),
+        };
+        let pair = (HeroKind::Mage, HeroKind::Warrior);
+        profiles.record("Ann Marie", pair, Outcome::Win);
+        profiles.record("Ann Marie", pair, Outcome::Win);
+        profiles.record("Ann Marie", pair, Outcome::Loss);
+        profiles.record("Bob", (HeroKind::Priest, HeroKind::Archer),
+            Outcome::Draw);
+        let text = profiles.to_string();
+
+        let parsed = parse_profiles(&text).unwrap();
+        assert_eq!(parsed.len(), 2);
+        let ann = &parsed[0];
+        assert_eq!(ann.get_name(), "Ann Marie");
+        assert_eq!(ann.get_record(),
+            Record { wins: 2, losses: 1, draws: 0 });
+        assert_eq!(ann.get_hero_record(HeroKind::Mage), ann.get_record());
+        assert_eq!(ann.get_streak(), 0);
+        assert_eq!(ann.get_best_streak(), 2);
+        assert_eq!(ann.favorite_pairs(),
+            vec![((HeroKind::Warrior, HeroKind::Mage), 3)]);
+        assert_eq!(parsed[1].get_name(), "Bob");
+
+        let again = Profiles { path: profiles.path.clone(), profiles: parsed };
+        assert_eq!(again.to_string(), text);
+    }
+
+    #[test]
+    fn bad_profiles() {
+        let bad_line
+            = format!("{}\nprofile Ann Marie\nrecord wins=1 losses=0 \
+                draws=0\nwins 1\n", PROFILES_HEADER);
+        assert_eq!(parse_profiles(&bad_line).unwrap_err(),
+            "line 4: unrecognized line 'wins 1'");
+        assert_eq!(parse_profiles("profile Ann\n").unwrap_err(),
+            "missing profiles header");
+        let orphan = format!("{}\nrecord wins=1 losses=0 draws=0\n",
+            PROFILES_HEADER);
+        assert_eq!(parse_profiles(&orphan).unwrap_err(),
+            "line 2: expected 'profile' first");
+    }
+}
@@ -0,0 +1,665 @@
+use std::{ fs, path::Path };
+use crate::engine::{
+    game::{ PlayerPos, Winner, EndReason, TieBreak },
+    hero::{ Hero, HeroKind },
+    player::{ Player, HeroPos },
+    rules::Rules,
+    wheel::{ Rolls, parse_rolls, fmt_rolls },
+    advisor::fmt_locks,
+    draft::{ DraftAction, DraftStep },
+};
+
+/// First line of every replay file.
+pub const REPLAY_HEADER: &str = "wheels-replay";
+
+/// A single set of locks chosen on a set of rolls.
+#[derive(Copy, Clone, Debug)]
+pub struct Spin {
+    /// The wheels as shown to the player.
+    pub rolls: Rolls,
+    /// The wheels the player locked before re-spinning.
+    pub locks: [bool; 5],
+    /// Number of spins remaining in the turn, including this one.
+    pub spins_left: usize,
+}
+
+/// Everything one player did in a single turn.
+#[derive(Clone, Debug)]
+pub struct PlayerRecord {
+    /// The player's state at the start of the turn.
+    pub state: Player,
+    /// Each set of locks chosen, in order.
+    pub spins: Vec<Spin>,
+    /// The final rolls resolved for the turn.
+    pub rolls: Option<Rolls>,
+}
+
+impl PlayerRecord {
+    pub fn new(state: Player) -> Self {
+        Self { state, spins: Vec::new(), rolls: None }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TurnRecord {
+    pub turn: usize,
+    pub p1: PlayerRecord,
+    pub p2: PlayerRecord,
+}
+
+impl TurnRecord {
+    pub fn get(&self, pos: PlayerPos) -> &PlayerRecord {
+        match pos {
+            PlayerPos::P1 => &self.p1,
+            PlayerPos::P2 => &self.p2,
+        }
+    }
+
+    pub fn get_mut(&mut self, pos: PlayerPos) -> &mut PlayerRecord {
+        match pos {
+            PlayerPos::P1 => &mut self.p1,
+            PlayerPos::P2 => &mut self.p2,
+        }
+    }
+}
+
+/// A player as listed in the header of a replay.
+#[derive(Clone, Debug)]
+pub struct ReplayPlayer {
+    pub name: String,
+    /// Whether the player was controlled by the computer.
+    pub cpu: bool,
+}
+
+/// Record of a whole match, sufficient to review every decision made in it.
+///
+/// Replays are stored as plain text, one record per line:
+/// ```text
+/// wheels-replay
+/// player P1 human Alice
+/// player P2 cpu CPU
+/// rules permanent-locks
+/// ban P1 Engineer
+/// ban P2 Assassin
+/// pick P1 Warrior
+/// pick P2 Priest
+/// pick P2 Archer
+/// pick P1 Mage
+/// turn 1
+/// state P1 crown=10 bulwark=0
+/// hero P1 L Warrior level=0 exp=0 energy=0 act=false
+/// hero P1 R Mage level=0 exp=0 energy=0 act=false
+/// state P2 crown=10 bulwark=0
+/// hero P2 L Priest level=0 exp=0 energy=0 act=false
+/// hero P2 R Archer level=0 exp=0 energy=0 act=false
+/// spin P1 2 S SS HH HH DD* lock 1,2
+/// spin P1 1 S SS S HH S lock 1,2,3
+/// rolls P1 S SS S HH S
+/// rolls P2 D DD D* HH SS*
+/// winner P1 crown
+/// ```
+/// where the `rules` line (see [`Rules`]) defaults to the standard rules if
+/// missing, and the `ban` and `pick` lines are present only if heroes were
+/// drafted.
+#[derive(Clone, Debug)]
+pub struct Replay {
+    pub p1: ReplayPlayer,
+    pub p2: ReplayPlayer,
+    /// The rules the match was played under.
+    pub rules: Rules,
+    /// Bans and picks, in order, if heroes were drafted.
+    pub draft: Vec<DraftStep>,
+    pub turns: Vec<TurnRecord>,
+    pub winner: Option<Winner>,
+}
+
+impl Replay {
+    pub fn new(p1: ReplayPlayer, p2: ReplayPlayer) -> Self {
+        Self {
+            p1,
+            p2,
+            rules: Rules::default(),
+            draft: Vec::new(),
+            turns: Vec::new(),
+            winner: None,
+        }
+    }
+
+    pub fn get_player(&self, pos: PlayerPos) -> &ReplayPlayer {
+        match pos {
+            PlayerPos::P1 => &self.p1,
+            PlayerPos::P2 => &self.p2,
+        }
+    }
+
+    /// Write to a file at `path`.
+    pub fn save<P>(&self, path: P) -> Result<(), String>
+    where P: AsRef<Path>
+    {
+        fs::write(path.as_ref(), self.to_string())
+            .map_err(|e| {
+                format!("error writing '{}': {}", path.as_ref().display(), e)
+            })
+    }
+
+    /// Read from a file at `path`.
+    pub fn load<P>(path: P) -> Result<Self, String>
+    where P: AsRef<Path>
+    {
+        fs::read_to_string(path.as_ref())
+            .map_err(|e| {
+                format!("error reading '{}': {}", path.as_ref().display(), e)
+            })?
+            .parse()
+    }
+}
+
+fn fmt_pos(pos: PlayerPos) -> &'static str {
+    match pos {
+        PlayerPos::P1 => "P1",
+        PlayerPos::P2 => "P2",
+    }
+}
+
+fn parse_pos(s: &str) -> Result<PlayerPos, String> {
+    match s {
+        "P1" => Ok(PlayerPos::P1),
+        "P2" => Ok(PlayerPos::P2),
+        _ => Err(format!("invalid player '{}'", s)),
+    }
+}
+
+fn parse_hero_pos(s: &str) -> Result<HeroPos, String> {
+    match s {
+        "L" => Ok(HeroPos::L),
+        "R" => Ok(HeroPos::R),
+        _ => Err(format!("invalid hero position '{}'", s)),
+    }
+}
+
+fn fmt_winner(winner: Winner) -> String {
+    let w
+        = match winner {
+            Winner::P1(_) => "P1",
+            Winner::P2(_) => "P2",
+            Winner::Draw(_) => "Draw",
+        };
+    let reason
+        = match winner.reason() {
+            EndReason::Crown => "crown",
+            EndReason::TurnLimit(TieBreak::Crown) => "turn-limit crown",
+            EndReason::TurnLimit(TieBreak::Bulwark) => "turn-limit bulwark",
+            EndReason::TurnLimit(TieBreak::Tied) => "turn-limit tied",
+            EndReason::Abandoned => "abandoned",
+        };
+    format!("{} {}", w, reason)
+}
+
+/// Parse a winner and the reason the game ended, which defaults to `crown` if
+/// missing.
+fn parse_winner(s: &str, reason: &[&str]) -> Result<Winner, String> {
+    let reason
+        = match reason {
+            [] | ["crown"] => EndReason::Crown,
+            ["turn-limit", "crown"] => EndReason::TurnLimit(TieBreak::Crown),
+            ["turn-limit", "bulwark"]
+                => EndReason::TurnLimit(TieBreak::Bulwark),
+            ["turn-limit", "tied"] => EndReason::TurnLimit(TieBreak::Tied),
+            ["abandoned"] => EndReason::Abandoned,
+            _ => {
+                return Err(
+                    format!("invalid end reason '{}'", reason.join(" ")));
+            },
+        };
+    match s {
+        "P1" => Ok(Winner::P1(reason)),
+        "P2" => Ok(Winner::P2(reason)),
+        "Draw" => Ok(Winner::Draw(reason)),
+        _ => Err(format!("invalid winner '{}'", s)),
+    }
+}
+
+/// Parse the value of a `key=value` token.
+pub(crate) fn parse_field<T>(token: &str, key: &str) -> Result<T, String>
+where T: std::str::FromStr
+{
+    token.strip_prefix(key)
+        .and_then(|rest| rest.strip_prefix('='))
+        .ok_or_else(|| format!("expected '{}=...', got '{}'", key, token))?
+        .parse()
+        .map_err(|_| format!("invalid value in '{}'", token))
+}
+
+/// Parse a comma-separated list of 1-indexed wheel numbers, or `none`.
+fn parse_locks(s: &str) -> Result<[bool; 5], String> {
+    let mut locks = [false; 5];
+    if s == "none" { return Ok(locks); }
+    for k in s.split(',') {
+        match k.parse::<usize>() {
+            Ok(k) if (1..=5).contains(&k) => { locks[k - 1] = true; },
+            _ => { return Err(format!("invalid lock '{}'", k)); },
+        }
+    }
+    Ok(locks)
+}
+
+/// Render `player`'s state as a `state` line followed by a `hero` line for
+/// each of its heroes.
+pub fn fmt_player_state(pos: PlayerPos, player: &Player) -> String {
+    let mut lines: Vec<String> = vec![
+        format!("state {} crown={} bulwark={}",
+            fmt_pos(pos), player.get_crown(), player.get_bulwark()),
+    ];
+    for (hpos, side) in [(HeroPos::L, "L"), (HeroPos::R, "R")] {
+        let hero = player.get_hero(hpos);
+        lines.push(format!(
+            "hero {} {} {} level={} exp={} energy={} act={}",
+            fmt_pos(pos),
+            side,
+            hero.get_kind(),
+            hero.get_level(),
+            hero.get_exp(),
+            hero.get_energy(),
+            hero.get_act(),
+        ));
+    }
+    lines.join("\n")
+}
+
+/// Partially read state for a single player.
+#[derive(Clone, Debug, Default)]
+struct PlayerState {
+    crown: Option<u8>,
+    bulwark: Option<u8>,
+    hero_l: Option<Hero>,
+    hero_r: Option<Hero>,
+}
+
+impl PlayerState {
+    fn finish(&self, name: &str) -> Result<Player, String> {
+        match (self.crown, self.bulwark, self.hero_l, self.hero_r) {
+            (Some(crown), Some(bulwark), Some(hero_l), Some(hero_r)) => {
+                let mut player = Player::new(name, hero_l, hero_r);
+                player.set_crown(crown)?;
+                player.set_bulwark(bulwark)?;
+                Ok(player)
+            },
+            _ => Err(format!("incomplete state for player '{}'", name)),
+        }
+    }
+}
+
+/// Reads the `state` and `hero` lines written by [`fmt_player_state`] back
+/// into a pair of [`Player`]s.
+#[derive(Clone, Debug, Default)]
+pub struct StateReader {
+    p1: PlayerState,
+    p2: PlayerState,
+}
+
+impl StateReader {
+    pub fn new() -> Self { Self::default() }
+
+    fn get_mut(&mut self, pos: PlayerPos) -> &mut PlayerState {
+        match pos {
+            PlayerPos::P1 => &mut self.p1,
+            PlayerPos::P2 => &mut self.p2,
+        }
+    }
+
+    /// Read a single line, returning `Ok(false)` if it is neither a `state`
+    /// nor a `hero` line.
+    pub fn read_line(&mut self, line: &str) -> Result<bool, String> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens.as_slice() {
+            ["state", pos, crown, bulwark] => {
+                let state = self.get_mut(parse_pos(pos)?);
+                state.crown = Some(parse_field(crown, "crown")?);
+                state.bulwark = Some(parse_field(bulwark, "bulwark")?);
+                Ok(true)
+            },
+            ["hero", pos, side, kind, level, exp, energy, act] => {
+                let kind
+                    = HeroKind::from_name(kind)
+                    .ok_or_else(|| format!("unknown hero '{}'", kind))?;
+                let mut hero = Hero::from_kind(kind);
+                hero.set_level(parse_field(level, "level")?)?;
+                hero.set_exp(parse_field(exp, "exp")?)?;
+                hero.set_energy(parse_field(energy, "energy")?)?;
+                hero.set_act(parse_field(act, "act")?);
+                let state = self.get_mut(parse_pos(pos)?);
+                match parse_hero_pos(side)? {
+                    HeroPos::L => { state.hero_l = Some(hero); },
+                    HeroPos::R => { state.hero_r = Some(hero); },
+                }
+                Ok(true)
+            },
+            _ => Ok(false),
+        }
+    }
+
+    /// Build both players from everything read so far.
+    pub fn finish(&self, name_p1: &str, name_p2: &str)
+        -> Result<(Player, Player), String>
+    {
+        Ok((self.p1.finish(name_p1)?, self.p2.finish(name_p2)?))
+    }
+}
+
+impl std::fmt::Display for Replay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", REPLAY_HEADER)?;
+        for pos in [PlayerPos::P1, PlayerPos::P2] {
+            let player = self.get_player(pos);
+            writeln!(f, "player {} {} {}",
+                fmt_pos(pos),
+                if player.cpu { "cpu" } else { "human" },
+                player.name,
+            )?;
+        }
+        writeln!(f, "rules {}", self.rules)?;
+        for step in self.draft.iter() {
+            writeln!(f, "{} {} {}",
+                step.action, fmt_pos(step.player), step.hero)?;
+        }
+        for turn in self.turns.iter() {
+            writeln!(f, "turn {}", turn.turn)?;
+            for pos in [PlayerPos::P1, PlayerPos::P2] {
+                writeln!(f, "{}", fmt_player_state(pos, &turn.get(pos).state))?;
+            }
+            for pos in [PlayerPos::P1, PlayerPos::P2] {
+                for spin in turn.get(pos).spins.iter() {
+                    writeln!(f, "spin {} {} {} lock {}",
+                        fmt_pos(pos),
+                        spin.spins_left,
+                        fmt_rolls(&spin.rolls),
+                        fmt_locks(&spin.locks),
+                    )?;
+                }
+            }
+            for pos in [PlayerPos::P1, PlayerPos::P2] {
+                if let Some(rolls) = turn.get(pos).rolls.as_ref() {
+                    writeln!(f, "rolls {} {}", fmt_pos(pos), fmt_rolls(rolls))?;
+                }
+            }
+        }
+        if let Some(winner) = self.winner {
+            writeln!(f, "winner {}", fmt_winner(winner))?;
+        }
+        Ok(())
+    }
+}
+
+/// A turn whose lines are still being read.
+struct PartialTurn {
+    turn: usize,
+    states: StateReader,
+    spins_p1: Vec<Spin>,
+    spins_p2: Vec<Spin>,
+    rolls_p1: Option<Rolls>,
+    rolls_p2: Option<Rolls>,
+}
+
+impl PartialTurn {
+    fn new(turn: usize) -> Self {
+        Self {
+            turn,
+            states: StateReader::new(),
+            spins_p1: Vec::new(),
+            spins_p2: Vec::new(),
+            rolls_p1: None,
+            rolls_p2: None,
+        }
+    }
+
+    fn finish(self, p1: &ReplayPlayer, p2: &ReplayPlayer)
+        -> Result<TurnRecord, String>
+    {
+        let (state_p1, state_p2) = self.states.finish(&p1.name, &p2.name)?;
+        Ok(TurnRecord {
+            turn: self.turn,
+            p1: PlayerRecord {
+                state: state_p1,
+                spins: self.spins_p1,
+                rolls: self.rolls_p1,
+            },
+            p2: PlayerRecord {
+                state: state_p2,
+                spins: self.spins_p2,
+                rolls: self.rolls_p2,
+            },
+        })
+    }
+}
+
+fn parse_player(kind: &str, name: &str) -> Result<ReplayPlayer, String> {
+    let cpu
+        = match kind {
+            "cpu" => true,
+            "human" => false,
+            _ => { return Err(format!("invalid player kind '{}'", kind)); },
+        };
+    Ok(ReplayPlayer { name: name.to_string(), cpu })
+}
+
+fn parse_spin(spins_left: &str, rest: &[&str]) -> Result<Spin, String> {
+    match rest {
+        [rolls @ .., "lock", locks] => {
+            Ok(Spin {
+                rolls: parse_rolls(&rolls.join(" "))?,
+                locks: parse_locks(locks)?,
+                spins_left: spins_left.parse()
+                    .map_err(|_| {
+                        format!("invalid spin count '{}'", spins_left)
+                    })?,
+            })
+        },
+        _ => Err("expected 'lock' in spin".to_string()),
+    }
+}
+
+impl std::str::FromStr for Replay {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().enumerate();
+        match lines.next() {
+            Some((_, REPLAY_HEADER)) => { },
+            _ => { return Err("missing replay header".to_string()); },
+        }
+        let mut p1: Option<ReplayPlayer> = None;
+        let mut p2: Option<ReplayPlayer> = None;
+        let mut rules = Rules::default();
+        let mut draft: Vec<DraftStep> = Vec::new();
+        let mut turns: Vec<TurnRecord> = Vec::new();
+        let mut current: Option<PartialTurn> = None;
+        let mut winner: Option<Winner> = None;
+
+        for (n, line) in lines {
+            let err = |e: String| format!("line {}: {}", n + 1, e);
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            match (tokens.as_slice(), current.as_mut()) {
+                ([], _) => { },
+                (["player", pos, kind, ..], _) => {
+                    let name: &str = line.splitn(4, ' ').nth(3).unwrap_or("");
+                    let player = parse_player(kind, name).map_err(err)?;
+                    match parse_pos(pos).map_err(err)? {
+                        PlayerPos::P1 => { p1 = Some(player); },
+                        PlayerPos::P2 => { p2 = Some(player); },
+                    }
+                },
+                (["rules", ..], None) => {
+                    let rest = line.trim().strip_prefix("rules").unwrap_or("");
+                    rules = rest.parse().map_err(err)?;
+                },
+                ([action @ ("ban" | "pick"), pos, hero], None) => {
+                    let action
+                        = match *action {
+                            "ban" => DraftAction::Ban,
+                            _ => DraftAction::Pick,
+                        };
+                    let hero
+                        = HeroKind::from_name(hero)
+                        .ok_or_else(|| format!("unknown hero '{}'", hero))
+                        .map_err(err)?;
+                    let player = parse_pos(pos).map_err(err)?;
+                    draft.push(DraftStep { player, action, hero });
+                },
+                (["turn", turn], _) => {
+                    let turn: usize
+                        = turn.parse()
+                        .map_err(|_| err(format!("invalid turn '{}'", turn)))?;
+                    let prev = current.replace(PartialTurn::new(turn));
+                    if let Some(prev) = prev {
+                        match (p1.as_ref(), p2.as_ref()) {
+                            (Some(p1), Some(p2)) => {
+                                turns.push(prev.finish(p1, p2).map_err(err)?);
+                            },
+                            _ => {
+                                return Err(err("missing player list".into()));
+                            },
+                        }
+                    }
+                },
+                (["spin", pos, spins_left, rest @ ..], Some(cur)) => {
+                    let spin = parse_spin(spins_left, rest).map_err(err)?;
+                    match parse_pos(pos).map_err(err)? {
+                        PlayerPos::P1 => { cur.spins_p1.push(spin); },
+                        PlayerPos::P2 => { cur.spins_p2.push(spin); },
+                    }
+                },
+                (["rolls", pos, rest @ ..], Some(cur)) => {
+                    let rolls = parse_rolls(&rest.join(" ")).map_err(err)?;
+                    match parse_pos(pos).map_err(err)? {
+                        PlayerPos::P1 => { cur.rolls_p1 = Some(rolls); },
+                        PlayerPos::P2 => { cur.rolls_p2 = Some(rolls); },
+                    }
+                },
+                (["winner", w, reason @ ..], _) => {
+                    winner = Some(parse_winner(w, reason).map_err(err)?);
+                },
+                (_, Some(cur)) => {
+                    if !cur.states.read_line(line).map_err(err)? {
+                        return Err(err(format!("unexpected line '{}'", line)));
+                    }
+                },
+                (_, None) => {
+                    return Err(err(format!("unexpected line '{}'", line)));
+                },
+            }
+        }
+        match (p1, p2) {
+            (Some(p1), Some(p2)) => {
+                if let Some(cur) = current {
+                    turns.push(cur.finish(&p1, &p2)?);
+                }
+                Ok(Self { p1, p2, rules, draft, turns, winner })
+            },
+            _ => Err("missing player list".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPLAY: &str = "\
+wheels-replay
+player P1 human Ann Marie
+player P2 cpu CPU
+rules permanent-locks max-turns=20
+ban P1 Engineer
+ban P2 Assassin
+pick P1 Warrior
+pick P2 Priest
+pick P2 Archer
+pick P1 Mage
+turn 1
+state P1 crown=10 bulwark=0
+hero P1 L Warrior level=0 exp=0 energy=0 act=false
+hero P1 R Mage level=0 exp=0 energy=0 act=false
+state P2 crown=10 bulwark=0
+hero P2 L Priest level=0 exp=0 energy=0 act=false
+hero P2 R Archer level=0 exp=0 energy=0 act=false
+spin P1 2 S SS HH HH DD* lock 1,2
+spin P1 1 S SS S HH S lock 1,2,3
+spin P2 2 D DD D* HH SS* lock none
+rolls P1 S SS S HH S
+rolls P2 D DD D* HH SS*
+turn 2
+state P1 crown=12 bulwark=5
+hero P1 L Warrior level=1 exp=3 energy=2 act=true
+hero P1 R Mage level=2 exp=0 energy=1 act=false
+state P2 crown=1 bulwark=0
+hero P2 L Priest level=0 exp=4 energy=0 act=false
+hero P2 R Archer level=0 exp=0 energy=0 act=false
+rolls P2 HHH S* D D SS*
+winner P2 turn-limit bulwark
+";
+
+    #[test]
+    fn round_trip() {
+        let replay: Replay = REPLAY.parse().unwrap();
+        assert_eq!(replay.p1.name, "Ann Marie");
+        assert!(!replay.p1.cpu && replay.p2.cpu);
+        assert!(replay.rules.permanent_locks);
+        assert_eq!(replay.rules.max_turns, Some(20));
+        assert_eq!(replay.draft.len(), 6);
+        assert_eq!(replay.draft[2], DraftStep {
+            player: PlayerPos::P1,
+            action: DraftAction::Pick,
+            hero: HeroKind::Warrior,
+        });
+        assert_eq!(replay.turns.len(), 2);
+        let first = &replay.turns[0];
+        assert_eq!(first.p1.spins.len(), 2);
+        assert_eq!(first.p1.spins[1].spins_left, 1);
+        assert_eq!(first.p1.spins[1].locks, [true, true, true, false, false]);
+        assert_eq!(first.p2.spins[0].locks, [false; 5]);
+        let second = &replay.turns[1];
+        assert_eq!(second.turn, 2);
+        assert_eq!(second.p1.state.get_name(), "Ann Marie");
+        assert_eq!(second.p1.state.get_bulwark(), 5);
+        assert!(second.p1.state.get_hero(HeroPos::L).get_act());
+        assert!(second.p1.rolls.is_none());
+        assert_eq!(replay.winner,
+            Some(Winner::P2(EndReason::TurnLimit(TieBreak::Bulwark))));
+
+        assert_eq!(replay.to_string(), REPLAY);
+    }
+
+    #[test]
+    fn rules_default_to_standard() {
+        let text = REPLAY.replace("rules permanent-locks max-turns=20\n", "");
+        let replay: Replay = text.parse().unwrap();
+        assert_eq!(replay.rules, Rules::default());
+        assert!(replay.to_string().contains("\nrules standard\n"));
+    }
+
+    #[test]
+    fn winner_reason_defaults_to_crown() {
+        let text = REPLAY.replace("P2 turn-limit bulwark", "Draw");
+        let replay: Replay = text.parse().unwrap();
+        assert_eq!(replay.winner, Some(Winner::Draw(EndReason::Crown)));
+        assert!(replay.to_string().ends_with("winner Draw crown\n"));
+    }
+
+    #[test]
+    fn bad_replays() {
+        let err = |text: &str| text.parse::<Replay>().unwrap_err();
+        let bad_rules = REPLAY.replace("max-turns=20", "max-turns");
+        assert_eq!(err(&bad_rules), "line 4: invalid rule 'max-turns'");
+        assert_eq!(err("player P1 human Ann\n"), "missing replay header");
+        let bad_spin = REPLAY.replace("lock 1,2\n", "lock 1,9\n");
+        assert_eq!(err(&bad_spin), "line 18: invalid lock '9'");
+        let bad_winner = REPLAY.replace("turn-limit bulwark", "timeout");
+        assert_eq!(err(&bad_winner), "line 31: invalid end reason 'timeout'");
+        let no_players
+            = REPLAY.lines()
+            .filter(|line| !line.starts_with("player"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert_eq!(err(&no_players), "line 21: missing player list");
+    }
+}
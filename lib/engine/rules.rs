@@ -0,0 +1,126 @@
+use crate::engine::game::PlayerPos;
+
+/// How the players share each turn.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum TurnMode {
+    /// Both players' rolls resolve every turn. In two-player games only the
+    /// player whose turn it is may lock and re-spin; the other player's rolls
+    /// are a single unlocked spin.
+    #[default]
+    BothRoll,
+    /// Players take turns, and only the rolls of the player whose turn it is
+    /// resolve. Player 1 takes odd-numbered turns.
+    Alternating,
+}
+
+impl TurnMode {
+    /// Whether `player`'s rolls resolve on turn number `turn`.
+    pub fn is_active(&self, player: PlayerPos, turn: usize) -> bool {
+        match (self, player) {
+            (Self::BothRoll, _) => true,
+            (Self::Alternating, PlayerPos::P1) => !turn.is_multiple_of(2),
+            (Self::Alternating, PlayerPos::P2) => turn.is_multiple_of(2),
+        }
+    }
+}
+
+/// Optional variations on the standard rules.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Rules {
+    pub turn_mode: TurnMode,
+    /// If `true`, a wheel stays locked for the rest of the turn once locked,
+    /// as in the original game. Otherwise locks may be toggled freely between
+    /// spins.
+    pub permanent_locks: bool,
+    /// If `true`, EXP beyond a level up and energy beyond a full rod carry
+    /// over toward the next level or rod instead of being discarded.
+    pub carry_over: bool,
+    /// If set, the game ends after this many turns, won by the player with
+    /// the higher Crown, or failing that the higher Bulwark.
+    pub max_turns: Option<usize>,
+    /// If set, bombs deal 1 more damage for every turn after this one.
+    pub sudden_death: Option<usize>,
+}
+
+/// Written as the rule variants in play, e.g. `alternating carry-over
+/// max-turns=20`, or `standard` if none are.
+impl std::fmt::Display for Rules {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut rules: Vec<String> = Vec::new();
+        if self.turn_mode == TurnMode::Alternating {
+            rules.push("alternating".to_string());
+        }
+        if self.permanent_locks { rules.push("permanent-locks".to_string()); }
+        if self.carry_over { rules.push("carry-over".to_string()); }
+        if let Some(turns) = self.max_turns {
+            rules.push(format!("max-turns={}", turns));
+        }
+        if let Some(turn) = self.sudden_death {
+            rules.push(format!("sudden-death={}", turn));
+        }
+        if rules.is_empty() {
+            write!(f, "standard")
+        } else {
+            write!(f, "{}", rules.join(" "))
+        }
+    }
+}
+
+/// Parses the format written by [`Rules`]' `Display`; an empty string is also
+/// the standard rules.
+impl std::str::FromStr for Rules {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rules = Self::default();
+        for rule in s.split_whitespace() {
+            let (key, value) = rule.split_once('=').unwrap_or((rule, ""));
+            let turns = || -> Result<usize, String> {
+                value.parse().map_err(|_| format!("invalid rule '{}'", rule))
+            };
+            match key {
+                "standard" => { },
+                "alternating" => { rules.turn_mode = TurnMode::Alternating; },
+                "permanent-locks" => { rules.permanent_locks = true; },
+                "carry-over" => { rules.carry_over = true; },
+                "max-turns" => { rules.max_turns = Some(turns()?); },
+                "sudden-death" => { rules.sudden_death = Some(turns()?); },
+                _ => { return Err(format!("invalid rule '{}'", rule)); },
+            }
+        }
+        Ok(rules)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let rules = Rules {
+            turn_mode: TurnMode::Alternating,
+            permanent_locks: true,
+            carry_over: true,
+            max_turns: Some(20),
+            sudden_death: Some(10),
+        };
+        let s = "alternating permanent-locks carry-over max-turns=20 \
+            sudden-death=10";
+        assert_eq!(rules.to_string(), s);
+        assert_eq!(s.parse::<Rules>(), Ok(rules));
+        assert_eq!(Rules::default().to_string(), "standard");
+        assert_eq!("standard".parse::<Rules>(), Ok(Rules::default()));
+        assert_eq!("".parse::<Rules>(), Ok(Rules::default()));
+    }
+
+    #[test]
+    fn bad_rules() {
+        assert_eq!("max-turns=ten".parse::<Rules>(),
+            Err("invalid rule 'max-turns=ten'".to_string()));
+        assert_eq!("sudden-death".parse::<Rules>(),
+            Err("invalid rule 'sudden-death'".to_string()));
+        assert_eq!("fast".parse::<Rules>(),
+            Err("invalid rule 'fast'".to_string()));
+    }
+}
@@ -0,0 +1,193 @@
+use crate::engine::game::{ PlayerPos, Winner };
+
+/// The result of one game in a [`Series`].
+#[derive(Copy, Clone, Debug)]
+pub struct SeriesGame {
+    /// Which side sat as Player 1.
+    pub p1: usize,
+    pub winner: Winner,
+    pub turns: usize,
+}
+
+impl SeriesGame {
+    /// The side that won, if not a draw.
+    pub fn winning_side(&self) -> Option<usize> {
+        self.winner.player()
+            .map(|pos| if pos == PlayerPos::P1 { self.p1 } else { 1 - self.p1 })
+    }
+}
+
+/// A best-of-N series of games between two sides, who swap seats after every
+/// game.
+///
+/// Sides are numbered 0 and 1, with side 0 sitting as Player 1 in the first
+/// game. Drawn games don't count toward either side, so a series ends only
+/// once one side has won a majority of `best_of`.
+#[derive(Clone, Debug)]
+pub struct Series {
+    best_of: usize,
+    names: [String; 2],
+    games: Vec<SeriesGame>,
+}
+
+impl Series {
+    pub fn new(best_of: usize, name_0: &str, name_1: &str)
+        -> Result<Self, String>
+    {
+        if best_of.is_multiple_of(2) {
+            return Err(format!(
+                "a series must be best of an odd number, got {}", best_of));
+        }
+        Ok(Self {
+            best_of,
+            names: [name_0.to_string(), name_1.to_string()],
+            games: Vec::new(),
+        })
+    }
+
+    pub fn get_best_of(&self) -> usize { self.best_of }
+
+    pub fn get_name(&self, side: usize) -> &str { &self.names[side] }
+
+    pub fn get_games(&self) -> &[SeriesGame] { &self.games }
+
+    /// Number of wins needed to take the series.
+    pub fn needed(&self) -> usize { self.best_of / 2 + 1 }
+
+    pub fn wins(&self, side: usize) -> usize {
+        self.games.iter()
+            .filter(|game| game.winning_side() == Some(side))
+            .count()
+    }
+
+    pub fn draws(&self) -> usize {
+        self.games.iter()
+            .filter(|game| game.winning_side().is_none())
+            .count()
+    }
+
+    /// The side that has won the series, if any.
+    pub fn winner(&self) -> Option<usize> {
+        (0..2).find(|side| self.wins(*side) >= self.needed())
+    }
+
+    pub fn is_over(&self) -> bool { self.winner().is_some() }
+
+    /// Where `side` sits in the next game.
+    pub fn next_seat(&self, side: usize) -> PlayerPos {
+        if (self.games.len() + side).is_multiple_of(2) {
+            PlayerPos::P1
+        } else {
+            PlayerPos::P2
+        }
+    }
+
+    /// Record the result of a game played with `side` sitting as Player 1.
+    pub fn record(&mut self, p1: usize, winner: Winner, turns: usize) {
+        self.games.push(SeriesGame { p1, winner, turns });
+    }
+
+    /// The current score, e.g. `Alice 2 - 1 CPU (best of 5, 1 draw)`.
+    pub fn scoreboard(&self) -> String {
+        let draws
+            = match self.draws() {
+                0 => String::new(),
+                1 => ", 1 draw".to_string(),
+                n => format!(", {} draws", n),
+            };
+        format!("{} {} - {} {} (best of {}{})",
+            self.names[0], self.wins(0), self.wins(1), self.names[1],
+            self.best_of, draws)
+    }
+
+    /// One line per game played, followed by the final score.
+    pub fn summary(&self) -> String {
+        let mut lines: Vec<String>
+            = self.games.iter().enumerate()
+            .map(|(k, game)| {
+                let result
+                    = match game.winning_side() {
+                        Some(side) => format!("{} wins", self.names[side]),
+                        None => "draw".to_string(),
+                    };
+                let turns
+                    = if game.turns == 1 { "turn" } else { "turns" };
+                format!("Game {}: {} as Player 1, {} {}, {} ({})",
+                    k + 1, self.names[game.p1], game.turns, turns, result,
+                    game.winner.reason())
+            })
+            .collect();
+        lines.push(self.scoreboard());
+        if let Some(side) = self.winner() {
+            lines.push(format!("{} wins the series!", self.names[side]));
+        }
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::game::{ EndReason, TieBreak };
+    use super::*;
+
+    const P1_WINS: Winner = Winner::P1(EndReason::Crown);
+    const P2_WINS: Winner = Winner::P2(EndReason::Crown);
+    const DRAW: Winner = Winner::Draw(EndReason::TurnLimit(TieBreak::Tied));
+
+    /// Record a game in `series`, seating both sides as for the next game.
+    fn play(series: &mut Series, winner: Winner) {
+        let p1 = if series.next_seat(0) == PlayerPos::P1 { 0 } else { 1 };
+        series.record(p1, winner, 10);
+    }
+
+    #[test]
+    fn rejects_even_best_of() {
+        assert_eq!(
+            Series::new(4, "Alice", "Bob").unwrap_err(),
+            "a series must be best of an odd number, got 4",
+        );
+        assert!(Series::new(0, "Alice", "Bob").is_err());
+        assert!(Series::new(1, "Alice", "Bob").is_ok());
+    }
+
+    #[test]
+    fn seats_alternate() {
+        let mut series = Series::new(5, "Alice", "Bob").unwrap();
+        for k in 0..4 {
+            let seat_0 = series.next_seat(0);
+            assert_eq!(
+                seat_0,
+                if k % 2 == 0 { PlayerPos::P1 } else { PlayerPos::P2 },
+            );
+            assert_eq!(series.next_seat(1), seat_0.other());
+            play(&mut series, DRAW);
+        }
+    }
+
+    #[test]
+    fn draws_do_not_count() {
+        let mut series = Series::new(3, "Alice", "Bob").unwrap();
+        for _ in 0..3 { play(&mut series, DRAW); }
+        assert_eq!((series.wins(0), series.wins(1), series.draws()), (0, 0, 3));
+        assert!(!series.is_over());
+        assert_eq!(series.scoreboard(), "Alice 0 - 0 Bob (best of 3, 3 draws)");
+    }
+
+    #[test]
+    fn ends_at_majority() {
+        let mut series = Series::new(5, "Alice", "Bob").unwrap();
+        assert_eq!(series.needed(), 3);
+        // side 0 sits as Player 1 in games 1, 3 and 5
+        play(&mut series, P1_WINS);
+        play(&mut series, P1_WINS);
+        play(&mut series, DRAW);
+        assert_eq!((series.wins(0), series.wins(1)), (1, 1));
+        play(&mut series, P2_WINS);
+        assert_eq!((series.wins(0), series.wins(1)), (2, 1));
+        assert!(!series.is_over());
+        play(&mut series, P1_WINS);
+        assert_eq!(series.winner(), Some(0));
+        assert!(series.is_over());
+        assert_eq!(series.scoreboard(), "Alice 3 - 1 Bob (best of 5, 1 draw)");
+    }
+}
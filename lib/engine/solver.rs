@@ -0,0 +1,615 @@
+use std::collections::{ HashMap, HashSet };
+use rand::{ RngCore, SeedableRng, rngs::StdRng };
+use crate::engine::{
+    game::{ Game, NoLog, PlayerPos, Winner },
+    hero::{ Hero, HeroKind, MAX_EXP },
+    player::{ Player, HeroPos, MAX_BULWARK, MAX_CROWN },
+    rules::{ Rules, TurnMode },
+    strategy::Strategy,
+    wheel::{ Wheel, Rolls, NUM_FACES },
+};
+
+/// Default limit on the number of states a [`Solver`] will explore.
+pub const MAX_STATES: usize = 10_000;
+
+/// Stop iterating once no probability changes by more than this.
+const TOLERANCE: f64 = 1e-12;
+
+/// Give up iterating after this many sweeps over the state space.
+const MAX_SWEEPS: usize = 100_000;
+
+/// Number of possible sets of rolls.
+const NUM_ROLLS: usize = (NUM_FACES as usize).pow(5);
+
+/// Probabilities of each way a game can end.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct WinProbs {
+    pub p1: f64,
+    pub p2: f64,
+    pub draw: f64,
+}
+
+impl WinProbs {
+    fn of(winner: Winner) -> Self {
+        match winner {
+            Winner::P1(_) => Self { p1: 1.0, ..Self::default() },
+            Winner::P2(_) => Self { p2: 1.0, ..Self::default() },
+            Winner::Draw(_) => Self { draw: 1.0, ..Self::default() },
+        }
+    }
+
+    /// Probability that the player at `pos` wins.
+    pub fn get(&self, pos: PlayerPos) -> f64 {
+        match pos {
+            PlayerPos::P1 => self.p1,
+            PlayerPos::P2 => self.p2,
+        }
+    }
+
+    fn add_scaled(&mut self, other: &Self, p: f64) {
+        self.p1 += p * other.p1;
+        self.p2 += p * other.p2;
+        self.draw += p * other.draw;
+    }
+
+    fn max_diff(&self, other: &Self) -> f64 {
+        (self.p1 - other.p1).abs()
+            .max((self.p2 - other.p2).abs())
+            .max((self.draw - other.draw).abs())
+    }
+}
+
+/// Crown, Bulwark, and each hero's level, EXP, energy and act flag.
+type PlayerKey = [u8; 10];
+
+/// A turn number and whose turn it is, as far as they matter to the rules,
+/// and both players' keys.
+type StateKey = ((usize, usize), [u8; 20]);
+
+fn player_key(player: &Player) -> PlayerKey {
+    let mut key: PlayerKey = [0; 10];
+    key[0] = player.get_crown();
+    key[1] = player.get_bulwark();
+    for (k, pos) in [HeroPos::L, HeroPos::R].into_iter().enumerate() {
+        let hero = player.get_hero(pos);
+        key[2 + 4 * k] = hero.get_level();
+        key[3 + 4 * k] = hero.get_exp();
+        key[4 + 4 * k] = hero.get_energy();
+        key[5 + 4 * k] = hero.get_act().into();
+    }
+    key
+}
+
+/// The part of the turn number that can change the outcome of later turns.
+fn turn_key(rules: &Rules, turn: usize) -> (usize, usize) {
+    if rules.max_turns.is_some() {
+        (turn, 0)
+    } else if let Some(start) = rules.sudden_death {
+        // past this, every bomb empties a Crown, but players still take turns
+        let parity
+            = match rules.turn_mode {
+                TurnMode::Alternating => turn % 2,
+                TurnMode::BothRoll => 0,
+            };
+        (turn.min(start + MAX_CROWN as usize), parity)
+    } else {
+        (0, turn % 2)
+    }
+}
+
+fn state_key(game: &Game) -> StateKey {
+    let mut key = [0; 20];
+    key[..10].copy_from_slice(&player_key(game.get_player(PlayerPos::P1)));
+    key[10..].copy_from_slice(&player_key(game.get_player(PlayerPos::P2)));
+    (turn_key(game.get_rules(), game.get_turn()), key)
+}
+
+/// The parts of a game that stay fixed from turn to turn.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Setup {
+    heroes: [HeroKind; 4],
+    respins: [usize; 2],
+    rules: Rules,
+}
+
+impl Setup {
+    fn new(game: &Game) -> Self {
+        let p1 = game.get_player(PlayerPos::P1);
+        let p2 = game.get_player(PlayerPos::P2);
+        Self {
+            heroes: [
+                p1.get_hero(HeroPos::L).get_kind(),
+                p1.get_hero(HeroPos::R).get_kind(),
+                p2.get_hero(HeroPos::L).get_kind(),
+                p2.get_hero(HeroPos::R).get_kind(),
+            ],
+            respins: [
+                p1.get_handicap().get_respins(),
+                p2.get_handicap().get_respins(),
+            ],
+            rules: *game.get_rules(),
+        }
+    }
+}
+
+fn decode_rolls(mut idx: usize) -> Rolls {
+    let faces = NUM_FACES as usize;
+    std::array::from_fn(|k| {
+        let face = (idx % faces) as u8;
+        idx /= faces;
+        Wheel::sides(k, face)
+    })
+}
+
+/// Re-spin every wheel not in `mask` in a distribution over rolls.
+fn respin_unlocked(dist: &mut [f64], mask: usize) {
+    let faces = NUM_FACES as usize;
+    for k in (0..5).filter(|k| mask & (1 << k) == 0) {
+        let stride = faces.pow(k as u32);
+        let bases
+            = (0..NUM_ROLLS).filter(|idx| (idx / stride).is_multiple_of(faces));
+        for base in bases {
+            let total: f64
+                = (0..faces).map(|f| dist[base + f * stride]).sum();
+            let each = total / faces as f64;
+            (0..faces).for_each(|f| { dist[base + f * stride] = each; });
+        }
+    }
+}
+
+/// Exact distribution over the final rolls of `player` locking by `strategy`
+/// with `respins` re-spins, indexed by the face of each wheel.
+fn final_rolls(player: &Player, strategy: Strategy, respins: usize)
+    -> Vec<f64>
+{
+    let mut dist: Vec<f64> = vec![1.0 / NUM_ROLLS as f64; NUM_ROLLS];
+    // locks chosen independently of the panels leave every face equally
+    // likely
+    if matches!(strategy, Strategy::Idle | Strategy::Random) { return dist; }
+    let mut rng = StdRng::seed_from_u64(0);
+    for spins_left in (1..=respins).rev() {
+        let mut by_mask: Vec<Vec<f64>> = vec![Vec::new(); 32];
+        for (idx, p) in dist.iter().enumerate().filter(|(_, p)| **p > 0.0) {
+            let rolls = decode_rolls(idx);
+            let mut locks = [false; 5];
+            strategy.get_locks(
+                player, &rolls, spins_left, &mut locks, false, &mut rng);
+            let mask: usize
+                = locks.iter().enumerate()
+                .filter(|(_, lock)| **lock)
+                .map(|(k, _)| 1 << k)
+                .sum();
+            let bucket = &mut by_mask[mask];
+            if bucket.is_empty() { *bucket = vec![0.0; NUM_ROLLS]; }
+            bucket[idx] += p;
+        }
+        dist.iter_mut().for_each(|p| { *p = 0.0; });
+        for (mask, mut bucket) in by_mask.into_iter().enumerate() {
+            if bucket.is_empty() { continue; }
+            respin_unlocked(&mut bucket, mask);
+            dist.iter_mut().zip(bucket)
+                .for_each(|(p, q)| { *p += q; });
+        }
+    }
+    dist
+}
+
+/// Panel totals as far as they can change `player`'s state: energy, EXP and
+/// Bulwark beyond what it takes to fill a rod, level up or max out Bulwark
+/// all have the same effect.
+fn roll_class(rolls: &Rolls, player: &Player, carry_over: bool) -> [u8; 5] {
+    let totals = Wheel::totals(rolls);
+    let hero_l = player.get_hero(HeroPos::L);
+    let hero_r = player.get_hero(HeroPos::R);
+    let energy_cap = |hero: &Hero| {
+        let left = hero.get_energy_left().max(1);
+        if carry_over { left + hero.get_rod_len() - 1 } else { left }
+    };
+    let exp_cap = |exp: u8| {
+        let left = MAX_EXP - exp;
+        if carry_over { left + MAX_EXP - 1 } else { left }
+    };
+    [
+        totals.squares.saturating_sub(2).min(energy_cap(hero_l)),
+        totals.diamonds.saturating_sub(2).min(energy_cap(hero_r)),
+        totals.hammers.saturating_sub(2)
+            .min(MAX_BULWARK - player.get_bulwark()),
+        totals.exp_l.min(exp_cap(hero_l.get_exp())),
+        totals.exp_r.min(exp_cap(hero_r.get_exp())),
+    ]
+}
+
+/// An [`RngCore`] that plays back a fixed series of coin flips, noting if
+/// it runs out.
+struct ScriptRng<'a> {
+    script: &'a [bool],
+    pos: usize,
+    exhausted: bool,
+}
+
+impl RngCore for ScriptRng<'_> {
+    fn next_u32(&mut self) -> u32 {
+        let flip
+            = match self.script.get(self.pos) {
+                Some(flip) => *flip,
+                None => { self.exhausted = true; false },
+            };
+        self.pos += 1;
+        // `Rng::gen::<bool>` reads the highest bit
+        if flip { 1 << 31 } else { 0 }
+    }
+
+    fn next_u64(&mut self) -> u64 { u64::from(self.next_u32()) << 32 }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(4) {
+            let bytes = self.next_u32().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8])
+        -> Result<(), rand::Error>
+    {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// One state's equation in [`Solver::iterate`].
+struct Row {
+    /// Contribution of every successor already solved.
+    fixed: WinProbs,
+    /// Probability of staying in the same state.
+    stay: f64,
+    /// Probability of moving to each unsolved state, by index.
+    rest: Vec<(usize, f64)>,
+}
+
+/// Everything that can happen from a state in one turn.
+#[derive(Clone, Debug, Default)]
+struct Transitions {
+    /// Probability of each way the game can end this turn.
+    ends: WinProbs,
+    /// Probability of reaching each state where the game continues.
+    next: Vec<(StateKey, f64)>,
+}
+
+/// Computes exact win probabilities for a fixed pair of [`Strategy`]s by
+/// dynamic programming over every reachable game state.
+///
+/// Transitions and solved probabilities are kept between calls to
+/// [`Self::solve`], so that solving many states of the same game is cheap
+/// after the first. Only states where no hero is about to act are supported,
+/// i.e. those between turns.
+///
+/// A single turn can lead to on the order of 10^5 distinct states, so in
+/// practice only positions close to the end of the game, such as the last
+/// turn or two before a turn limit, can be solved within [`MAX_STATES`].
+#[derive(Clone, Debug)]
+pub struct Solver {
+    strategies: [Strategy; 2],
+    max_states: usize,
+    setup: Option<Setup>,
+    rolls: [HashMap<PlayerKey, Vec<(Rolls, f64)>>; 2],
+    transitions: HashMap<StateKey, Transitions>,
+    values: HashMap<StateKey, WinProbs>,
+}
+
+impl Solver {
+    pub fn new(strategy_p1: Strategy, strategy_p2: Strategy) -> Self {
+        Self {
+            strategies: [strategy_p1, strategy_p2],
+            max_states: MAX_STATES,
+            setup: None,
+            rolls: [HashMap::new(), HashMap::new()],
+            transitions: HashMap::new(),
+            values: HashMap::new(),
+        }
+    }
+
+    /// Set the number of states to explore before giving up.
+    pub fn max_states(mut self, max_states: usize) -> Self {
+        self.max_states = max_states;
+        self
+    }
+
+    /// Number of states explored so far.
+    pub fn num_states(&self) -> usize { self.transitions.len() }
+
+    fn strategy(&self, pos: PlayerPos) -> Strategy {
+        match pos {
+            PlayerPos::P1 => self.strategies[0],
+            PlayerPos::P2 => self.strategies[1],
+        }
+    }
+
+    /// Probabilities of each way the game can end, playing on from `game`.
+    pub fn solve(&mut self, game: &Game) -> Result<WinProbs, String> {
+        let setup = Setup::new(game);
+        if setup.rules.permanent_locks
+            && self.strategies.iter().any(|strat| {
+                matches!(strat, Strategy::Classic | Strategy::Advisor)
+            })
+        {
+            return Err(
+                "permanent locks are only supported with the idle and random \
+                strategies".to_string());
+        }
+        if self.setup != Some(setup) {
+            self.rolls.iter_mut().for_each(|rolls| rolls.clear());
+            self.transitions.clear();
+            self.values.clear();
+            self.setup = Some(setup);
+        }
+        let start = state_key(game);
+        if let Some(probs) = self.values.get(&start) { return Ok(*probs); }
+        let order = self.explore(game)?;
+        self.iterate(&order)?;
+        Ok(self.values[&start])
+    }
+
+    /// Find every state reachable from `game` that hasn't been solved yet, in
+    /// order of discovery.
+    fn explore(&mut self, game: &Game) -> Result<Vec<StateKey>, String> {
+        let mut order: Vec<StateKey> = Vec::new();
+        let mut seen: HashSet<StateKey> = HashSet::new();
+        let mut frontier: Vec<(StateKey, Option<Game>)>
+            = vec![(state_key(game), Some(game.clone()))];
+        seen.insert(frontier[0].0);
+        let mut k = 0;
+        while k < frontier.len() {
+            // only keep games until their state has been expanded
+            let (key, game) = (frontier[k].0, frontier[k].1.take());
+            k += 1;
+            order.push(key);
+            if !self.transitions.contains_key(&key) {
+                let game = game.expect("unexplored state without a game");
+                let (transitions, games) = self.expand(&game);
+                self.transitions.insert(key, transitions);
+                for (next, game) in games {
+                    if !self.values.contains_key(&next) && seen.insert(next) {
+                        frontier.push((next, Some(game)));
+                    }
+                }
+            } else {
+                for (next, _) in self.transitions[&key].next.iter() {
+                    if !self.values.contains_key(next) && seen.insert(*next) {
+                        frontier.push((*next, None));
+                    }
+                }
+            }
+            if self.transitions.len() + frontier.len() - k > self.max_states {
+                return Err(format!(
+                    "state space exceeds {} states", self.max_states));
+            }
+        }
+        Ok(order)
+    }
+
+    /// Distribution over the final rolls of the player at `pos`, one set of
+    /// rolls standing in for every set with the same effect.
+    fn rolls_for(&mut self, game: &Game, pos: PlayerPos)
+        -> Vec<(Option<Rolls>, f64)>
+    {
+        let rules = game.get_rules();
+        if !rules.turn_mode.is_active(pos, game.get_turn() + 1) {
+            return vec![(None, 1.0)];
+        }
+        let player = game.get_player(pos);
+        let key = player_key(player);
+        let side = if pos == PlayerPos::P1 { 0 } else { 1 };
+        if let Some(rolls) = self.rolls[side].get(&key) {
+            return rolls.iter().map(|(r, p)| (Some(*r), *p)).collect();
+        }
+        let respins = player.get_handicap().get_respins();
+        let dist = final_rolls(player, self.strategy(pos), respins);
+        let mut classes: HashMap<[u8; 5], (Rolls, f64)> = HashMap::new();
+        for (idx, p) in dist.into_iter().enumerate().filter(|(_, p)| *p > 0.0)
+        {
+            let rolls = decode_rolls(idx);
+            let class = roll_class(&rolls, player, rules.carry_over);
+            classes.entry(class).or_insert((rolls, 0.0)).1 += p;
+        }
+        let mut rolls: Vec<(Rolls, f64)> = classes.into_values().collect();
+        // keep the order fixed so that results don't depend on hashing
+        rolls.sort_by_key(|(r, _)| roll_class(r, player, rules.carry_over));
+        let out = rolls.iter().map(|(r, p)| (Some(*r), *p)).collect();
+        self.rolls[side].insert(key, rolls);
+        out
+    }
+
+    /// Play out every possible turn from `game`.
+    fn expand(&mut self, game: &Game) -> (Transitions, Vec<(StateKey, Game)>) {
+        let rolls_p1 = self.rolls_for(game, PlayerPos::P1);
+        let rolls_p2 = self.rolls_for(game, PlayerPos::P2);
+        let mut ends = WinProbs::default();
+        let mut next: HashMap<StateKey, f64> = HashMap::new();
+        let mut games: Vec<(StateKey, Game)> = Vec::new();
+        for (r1, p1) in rolls_p1.iter() {
+            for (r2, p2) in rolls_p2.iter() {
+                // branch on every coin flip made during the turn
+                let mut scripts: Vec<Vec<bool>> = vec![Vec::new()];
+                while let Some(script) = scripts.pop() {
+                    let mut after = game.clone();
+                    let mut rng = ScriptRng {
+                        script: &script,
+                        pos: 0,
+                        exhausted: false,
+                    };
+                    let winner
+                        = after.do_turn_partial_log(
+                            r1.as_ref(), r2.as_ref(), &mut rng, &mut NoLog);
+                    if rng.exhausted {
+                        for flip in [false, true] {
+                            let mut longer = script.clone();
+                            longer.push(flip);
+                            scripts.push(longer);
+                        }
+                        continue;
+                    }
+                    let p = p1 * p2 * 0.5_f64.powi(script.len() as i32);
+                    match winner {
+                        Some(winner) => {
+                            ends.add_scaled(&WinProbs::of(winner), p);
+                        },
+                        None => {
+                            let key = state_key(&after);
+                            let q = next.entry(key).or_insert(0.0);
+                            if *q == 0.0 { games.push((key, after)); }
+                            *q += p;
+                        },
+                    }
+                }
+            }
+        }
+        let mut next: Vec<(StateKey, f64)> = next.into_iter().collect();
+        next.sort_by_key(|(key, _)| *key);
+        (Transitions { ends, next }, games)
+    }
+
+    /// Solve for every state in `order` by Gauss-Seidel iteration, taking
+    /// later states first.
+    fn iterate(&mut self, order: &[StateKey]) -> Result<(), String> {
+        let index: HashMap<StateKey, usize>
+            = order.iter().enumerate().map(|(k, key)| (*key, k)).collect();
+        // a successor is either another unsolved state or a known result
+        let rows: Vec<Row>
+            = order.iter()
+            .map(|key| {
+                let trans = &self.transitions[key];
+                let mut fixed = trans.ends;
+                let mut stay: f64 = 0.0;
+                let mut rest: Vec<(usize, f64)> = Vec::new();
+                for (next, p) in trans.next.iter() {
+                    if next == key {
+                        stay += p;
+                        continue;
+                    }
+                    match index.get(next) {
+                        Some(j) => { rest.push((*j, *p)); },
+                        None => { fixed.add_scaled(&self.values[next], *p); },
+                    }
+                }
+                Row { fixed, stay, rest }
+            })
+            .collect();
+        let mut values: Vec<WinProbs> = vec![WinProbs::default(); order.len()];
+        let mut converged = false;
+        for _ in 0..MAX_SWEEPS {
+            let mut diff: f64 = 0.0;
+            for k in (0..order.len()).rev() {
+                let row = &rows[k];
+                let mut value = row.fixed;
+                for (j, p) in row.rest.iter() {
+                    value.add_scaled(&values[*j], *p);
+                }
+                let scale
+                    = if row.stay < 1.0 {
+                        (1.0 - row.stay).recip()
+                    } else {
+                        0.0
+                    };
+                value = WinProbs {
+                    p1: value.p1 * scale,
+                    p2: value.p2 * scale,
+                    draw: value.draw * scale,
+                };
+                diff = diff.max(value.max_diff(&values[k]));
+                values[k] = value;
+            }
+            if diff < TOLERANCE {
+                converged = true;
+                break;
+            }
+        }
+        if !converged {
+            return Err(format!(
+                "win probabilities did not converge after {} sweeps",
+                MAX_SWEEPS));
+        }
+        self.values.extend(order.iter().copied().zip(values));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::builder::GameBuilder;
+    use super::*;
+
+    /// A position with a single turn left before the limit.
+    fn last_turn(crown_p1: u8, crown_p2: u8) -> Game {
+        GameBuilder::new(
+            (HeroKind::Warrior, HeroKind::Mage),
+            (HeroKind::Archer, HeroKind::Priest),
+        )
+        .rules(Rules { max_turns: Some(20), ..Rules::default() })
+        .turn(19)
+        .crown(PlayerPos::P1, crown_p1)
+        .crown(PlayerPos::P2, crown_p2)
+        .build()
+        .unwrap()
+    }
+
+    #[test]
+    fn solve_decided_position() {
+        // Player 2 can't deal 12 damage in one turn, so either Crown falls to
+        // 0 or the turn limit is reached with Player 1 ahead
+        let probs
+            = Solver::new(Strategy::Classic, Strategy::Classic)
+            .solve(&last_turn(MAX_CROWN, 1))
+            .unwrap();
+        let p1_wins = WinProbs { p1: 1.0, ..WinProbs::default() };
+        assert!(probs.max_diff(&p1_wins) < 1e-9, "{:?}", probs);
+    }
+
+    #[test]
+    fn solve_matches_simulation() {
+        let game = last_turn(5, 5);
+        let probs
+            = Solver::new(Strategy::Classic, Strategy::Classic)
+            .solve(&game)
+            .unwrap();
+        assert!((probs.p1 + probs.p2 + probs.draw - 1.0).abs() < 1e-9);
+        let games = 20_000;
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut counts = [0_usize; 3];
+        for _ in 0..games {
+            let mut g = game.clone();
+            match g.run_headless(Strategy::Classic, Strategy::Classic, &mut rng)
+                .0
+            {
+                Winner::P1(_) => { counts[0] += 1; },
+                Winner::P2(_) => { counts[1] += 1; },
+                Winner::Draw(_) => { counts[2] += 1; },
+            }
+        }
+        // within about four standard errors of the exact value
+        let frac = |n: usize| n as f64 / games as f64;
+        let simulated = WinProbs {
+            p1: frac(counts[0]),
+            p2: frac(counts[1]),
+            draw: frac(counts[2]),
+        };
+        assert!(probs.max_diff(&simulated) < 0.015,
+            "exact {:?}, simulated {:?}", probs, simulated);
+    }
+
+    #[test]
+    fn sudden_death_keeps_parity() {
+        let rules = Rules {
+            turn_mode: TurnMode::Alternating,
+            sudden_death: Some(10),
+            ..Rules::default()
+        };
+        let last = 10 + MAX_CROWN as usize;
+        assert_eq!(turn_key(&rules, last + 1), (last, 1));
+        assert_eq!(turn_key(&rules, last + 2), (last, 0));
+        assert_ne!(turn_key(&rules, last + 3), turn_key(&rules, last + 4));
+        assert_eq!(turn_key(&rules, last + 3), turn_key(&rules, last + 5));
+
+        let rules = Rules { turn_mode: TurnMode::BothRoll, ..rules };
+        assert_eq!(turn_key(&rules, last + 3), turn_key(&rules, last + 4));
+    }
+}
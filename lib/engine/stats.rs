@@ -0,0 +1,351 @@
+use crate::engine::{
+    game::{ Action, Damage, Game, LevelUpKind, PlayerPos, TurnLog },
+    hero::HeroKind,
+    player::{ HeroPos, MAX_BULWARK, MAX_CROWN },
+    wheel::{ Wheel, Rolls },
+};
+
+/// Running totals for a single hero over a match.
+#[derive(Copy, Clone, Debug)]
+pub struct HeroStats {
+    pub kind: HeroKind,
+    /// Number of times the hero acted.
+    pub acts: u32,
+    pub crown_dmg: u32,
+    pub bulwark_dmg: u32,
+    pub level_ups: u32,
+    /// Number of hits dealt, which may be more than one per act.
+    hits: u32,
+}
+
+impl HeroStats {
+    fn new(kind: HeroKind) -> Self {
+        Self {
+            kind,
+            acts: 0,
+            crown_dmg: 0,
+            bulwark_dmg: 0,
+            level_ups: 0,
+            hits: 0,
+        }
+    }
+}
+
+/// Running totals for a single player over a match.
+#[derive(Clone, Debug)]
+pub struct PlayerStats {
+    pub name: String,
+    pub heroes: [HeroStats; 2],
+    /// Number of bombs deployed.
+    pub bombs: u32,
+    pub bomb_dmg: u32,
+    /// Total hammers rolled.
+    pub hammers: u32,
+    /// Total energy removed from the opponent's heroes by an Assassin.
+    pub delay: u32,
+    /// Total Crown healed by a Priest.
+    pub healing: u32,
+    /// Crown at the start of the match and after every turn.
+    pub crowns: Vec<u8>,
+    /// Crown and Bulwark as of the last action recorded, so that damage and
+    /// healing count only what actually changed them.
+    crown: u8,
+    bulwark: u8,
+}
+
+impl PlayerStats {
+    fn new(game: &Game, pos: PlayerPos) -> Self {
+        let player = game.get_player(pos);
+        Self {
+            name: player.get_name().to_string(),
+            heroes: [
+                HeroStats::new(player.get_hero(HeroPos::L).get_kind()),
+                HeroStats::new(player.get_hero(HeroPos::R).get_kind()),
+            ],
+            bombs: 0,
+            bomb_dmg: 0,
+            hammers: 0,
+            delay: 0,
+            healing: 0,
+            crowns: vec![player.get_crown()],
+            crown: player.get_crown(),
+            bulwark: player.get_bulwark(),
+        }
+    }
+
+    fn hero_mut(&mut self, kind: HeroKind) -> Option<&mut HeroStats> {
+        self.heroes.iter_mut().find(|hero| hero.kind == kind)
+    }
+
+    /// Take up to `dmg` from the tracked Crown, returning how much it lost.
+    fn crown_dec(&mut self, dmg: u8) -> u32 {
+        let dec = dmg.min(self.crown);
+        self.crown -= dec;
+        dec as u32
+    }
+
+    /// Add up to `heal` to the tracked Crown, returning how much it gained.
+    fn crown_inc(&mut self, heal: u8) -> u32 {
+        let inc = heal.min(MAX_CROWN.saturating_sub(self.crown));
+        self.crown += inc;
+        inc as u32
+    }
+
+    /// Take up to `dmg` from the tracked Bulwark, returning how much it lost.
+    fn bulwark_dec(&mut self, dmg: u8) -> u32 {
+        let dec = dmg.min(self.bulwark);
+        self.bulwark -= dec;
+        dec as u32
+    }
+
+    fn bulwark_inc(&mut self, inc: u8) {
+        self.bulwark = self.bulwark.saturating_add(inc).min(MAX_BULWARK);
+    }
+}
+
+/// Statistics for a match, collected turn by turn from each [`TurnLog`].
+#[derive(Clone, Debug)]
+pub struct MatchStats {
+    pub p1: PlayerStats,
+    pub p2: PlayerStats,
+    pub turns: usize,
+}
+
+impl MatchStats {
+    /// Start collecting statistics from the current state of `game`.
+    pub fn new(game: &Game) -> Self {
+        Self {
+            p1: PlayerStats::new(game, PlayerPos::P1),
+            p2: PlayerStats::new(game, PlayerPos::P2),
+            turns: 0,
+        }
+    }
+
+    pub fn get(&self, pos: PlayerPos) -> &PlayerStats {
+        match pos {
+            PlayerPos::P1 => &self.p1,
+            PlayerPos::P2 => &self.p2,
+        }
+    }
+
+    fn get_mut(&mut self, pos: PlayerPos) -> &mut PlayerStats {
+        match pos {
+            PlayerPos::P1 => &mut self.p1,
+            PlayerPos::P2 => &mut self.p2,
+        }
+    }
+
+    /// Add a turn that resolved `rolls_p1` and `rolls_p2` to produce `log`,
+    /// leaving `game` in its state after the turn.
+    pub fn record_turn(
+        &mut self,
+        game: &Game,
+        rolls_p1: Option<&Rolls>,
+        rolls_p2: Option<&Rolls>,
+        log: &TurnLog,
+    ) {
+        self.turns += 1;
+        for action in log.iter() {
+            self.record_action(action);
+        }
+        let sides = [(PlayerPos::P1, rolls_p1), (PlayerPos::P2, rolls_p2)];
+        for (pos, rolls) in sides {
+            let hammers
+                = rolls.map(|r| Wheel::totals(r).hammers).unwrap_or(0);
+            let player = game.get_player(pos);
+            let stats = self.get_mut(pos);
+            stats.hammers += hammers as u32;
+            stats.crowns.push(player.get_crown());
+            stats.crown = player.get_crown();
+            stats.bulwark = player.get_bulwark();
+        }
+    }
+
+    fn record_action(&mut self, action: &Action) {
+        match *action {
+            Action::LevelUp(pos, kind, LevelUpKind::Up(_)) => {
+                if let Some(hero) = self.get_mut(pos).hero_mut(kind) {
+                    hero.level_ups += 1;
+                }
+            },
+            Action::AssassinDamage(pos, dmg) => {
+                let dmg = self.get_mut(pos.other()).crown_dec(dmg);
+                let stats = self.get_mut(pos);
+                if let Some(hero) = stats.hero_mut(HeroKind::Assassin) {
+                    hero.acts += 1;
+                    hero.crown_dmg += dmg;
+                }
+            },
+            Action::BulwarkUp(pos, inc) | Action::EngineerBuild(pos, inc) => {
+                self.get_mut(pos).bulwark_inc(inc);
+            },
+            Action::AssassinDelay(pos, _, delay) => {
+                self.get_mut(pos).delay += delay as u32;
+            },
+            Action::PriestHeal(pos, heal) => {
+                let stats = self.get_mut(pos);
+                stats.healing += stats.crown_inc(heal);
+                if let Some(hero) = stats.hero_mut(HeroKind::Priest) {
+                    hero.acts += 1;
+                }
+            },
+            Action::HeroDamage(pos, kind, damage) => {
+                // count only what the opponent actually lost
+                let opp = self.get_mut(pos.other());
+                let (crown_dmg, bulwark_dmg)
+                    = match damage {
+                        Damage::Crown(dmg) => (opp.crown_dec(dmg), 0),
+                        Damage::Bulwark(dmg) => (0, opp.bulwark_dec(dmg)),
+                    };
+                let stats = self.get_mut(pos);
+                let Some(hero) = stats.hero_mut(kind) else { return; };
+                hero.crown_dmg += crown_dmg;
+                hero.bulwark_dmg += bulwark_dmg;
+                hero.hits += 1;
+                // a Mage hits twice every time it acts
+                let hits_per_act
+                    = if kind == HeroKind::Mage { 2 } else { 1 };
+                if hero.hits.is_multiple_of(hits_per_act) { hero.acts += 1; }
+            },
+            Action::Bomb(pos, dmg) => {
+                let dmg = self.get_mut(pos.other()).crown_dec(dmg);
+                let stats = self.get_mut(pos);
+                stats.bombs += 1;
+                stats.bomb_dmg += dmg;
+            },
+            _ => { },
+        }
+    }
+}
+
+/// Characters for a sparkline, from lowest to highest.
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Draw `values` as a sparkline scaled from 0 to `max`.
+pub fn sparkline(values: &[u8], max: u8) -> String {
+    let top = SPARKS.len() - 1;
+    values.iter()
+        .map(|v| {
+            let k
+                = (*v.min(&max) as usize * top)
+                .div_ceil(max.max(1) as usize);
+            SPARKS[k]
+        })
+        .collect()
+}
+
+impl std::fmt::Display for MatchStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Match statistics ({} turns)", self.turns)?;
+        for (n, stats) in [(1, &self.p1), (2, &self.p2)] {
+            writeln!(f)?;
+            writeln!(f, "Player {} ({}):", n, stats.name)?;
+            for hero in stats.heroes.iter() {
+                writeln!(f,
+                    "  {:<8}  acted {:>2}x  Crown dmg {:>3}  \
+                    Bulwark dmg {:>3}  level-ups {}",
+                    hero.kind.to_string(), hero.acts, hero.crown_dmg,
+                    hero.bulwark_dmg, hero.level_ups)?;
+            }
+            writeln!(f, "  bombs {} ({} dmg), hammers {}, Assassin delay {}, \
+                Priest healing {}",
+                stats.bombs, stats.bomb_dmg, stats.hammers, stats.delay,
+                stats.healing)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "Crown over time:")?;
+        let w = self.p1.name.len().max(self.p2.name.len());
+        for stats in [&self.p1, &self.p2] {
+            writeln!(f, "  {:<w$} {:>2} {} {}",
+                stats.name,
+                stats.crowns.first().copied().unwrap_or(0),
+                sparkline(&stats.crowns, MAX_CROWN),
+                stats.crowns.last().copied().unwrap_or(0),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{ SeedableRng, rngs::StdRng };
+    use crate::engine::{
+        builder::GameBuilder,
+        wheel::parse_rolls,
+    };
+    use super::*;
+
+    /// Play one turn of `game` with the given rolls, recording it in fresh
+    /// statistics.
+    fn play_turn(mut game: Game, rolls_p1: &str, rolls_p2: &str)
+        -> MatchStats
+    {
+        let rolls_p1 = parse_rolls(rolls_p1).unwrap();
+        let rolls_p2 = parse_rolls(rolls_p2).unwrap();
+        let mut stats = MatchStats::new(&game);
+        let mut rng = StdRng::seed_from_u64(0);
+        let (_, log) = game.do_turn_rng(&rolls_p1, &rolls_p2, &mut rng);
+        stats.record_turn(&game, Some(&rolls_p1), Some(&rolls_p2), &log);
+        stats
+    }
+
+    #[test]
+    fn engineer_acts_once() {
+        let game
+            = GameBuilder::new(
+                (HeroKind::Engineer, HeroKind::Warrior),
+                (HeroKind::Archer, HeroKind::Mage),
+            )
+            .level(PlayerPos::P1, HeroPos::L, 2)
+            .energy(PlayerPos::P1, HeroPos::L, 3)
+            .crown(PlayerPos::P2, 3)
+            .build()
+            .unwrap();
+        let stats = play_turn(game, "S S S D H", "S D H S D");
+        let engineer = stats.p1.heroes[0];
+        assert_eq!(engineer.acts, 1);
+        // a level 2 Engineer deals 4, but only 3 Crown is left to take
+        assert_eq!(engineer.crown_dmg, 3);
+        assert_eq!(stats.p2.crowns, vec![3, 0]);
+    }
+
+    #[test]
+    fn bulwark_damage_counts_what_was_removed() {
+        let game
+            = GameBuilder::new(
+                (HeroKind::Engineer, HeroKind::Warrior),
+                (HeroKind::Archer, HeroKind::Mage),
+            )
+            .level(PlayerPos::P1, HeroPos::L, 2)
+            .energy(PlayerPos::P1, HeroPos::L, 3)
+            .bulwark(PlayerPos::P2, 1)
+            .build()
+            .unwrap();
+        let stats = play_turn(game, "S S S D H", "S D H S D");
+        let engineer = stats.p1.heroes[0];
+        assert_eq!(engineer.acts, 1);
+        // a level 2 Engineer deals 5 Bulwark damage, but only 1 is there
+        assert_eq!(engineer.bulwark_dmg, 1);
+        assert_eq!(engineer.crown_dmg, 0);
+    }
+
+    #[test]
+    fn healing_stops_at_max_crown() {
+        let game
+            = GameBuilder::new(
+                (HeroKind::Priest, HeroKind::Warrior),
+                (HeroKind::Archer, HeroKind::Mage),
+            )
+            .level(PlayerPos::P1, HeroPos::L, 2)
+            .energy(PlayerPos::P1, HeroPos::L, 3)
+            .crown(PlayerPos::P1, MAX_CROWN - 1)
+            .build()
+            .unwrap();
+        let stats = play_turn(game, "S S S D H", "S D H S D");
+        assert_eq!(stats.p1.heroes[0].acts, 1);
+        // a level 2 Priest heals 2, but the Crown is one short of full
+        assert_eq!(stats.p1.healing, 1);
+        assert_eq!(stats.p1.crowns, vec![MAX_CROWN - 1, MAX_CROWN]);
+    }
+}
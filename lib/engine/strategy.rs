@@ -0,0 +1,107 @@
+use rand::Rng;
+use crate::engine::{
+    advisor,
+    player::{ Player, HeroPos },
+    wheel::{ Wheel, WheelKind, Rolls },
+};
+
+/// Lock-choosing logic for a computer-controlled player.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Strategy {
+    /// Lock every wheel showing the panel kind that will soonest make a hero
+    /// act, or else the most common panel kind.
+    Classic,
+    /// Follow [`advisor::advise`].
+    Advisor,
+    /// Lock each wheel with probability 1/2.
+    Random,
+    /// Never lock anything.
+    Idle,
+}
+
+impl Strategy {
+    /// All strategies.
+    pub fn all() -> [Self; 4] {
+        [Self::Classic, Self::Advisor, Self::Random, Self::Idle]
+    }
+
+    /// Look up a strategy by its (case-insensitive) name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::all().into_iter()
+            .find(|strat| strat.to_string().eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// Parse the strategies of both players written as `p1,p2`, e.g.
+    /// `classic,random`.
+    pub fn pair_from_name(list: &str) -> Result<(Self, Self), String> {
+        let strategy = |name: &str| -> Result<Self, String> {
+            Self::from_name(name)
+                .ok_or_else(|| format!("unknown strategy '{}'", name))
+        };
+        let (a, b)
+            = list.split_once(',')
+            .ok_or_else(|| format!("expected two strategies in '{}'", list))?;
+        Ok((strategy(a)?, strategy(b)?))
+    }
+
+    /// Choose locks for `player` on `rolls` with `spins_left` spins remaining
+    /// in the turn.
+    ///
+    /// `locks` holds the locks chosen on the previous spin, if any. If
+    /// `permanent` is `true`, none of these are unlocked.
+    pub fn get_locks<R>(
+        &self,
+        player: &Player,
+        rolls: &Rolls,
+        spins_left: usize,
+        locks: &mut [bool; 5],
+        permanent: bool,
+        rng: &mut R,
+    )
+    where R: Rng + ?Sized
+    {
+        let locked: [bool; 5] = if permanent { *locks } else { [false; 5] };
+        match self {
+            Self::Classic => {
+                locks.iter_mut().for_each(|lock| { *lock = false; });
+                let energy_left_l
+                    = player.get_hero(HeroPos::L).get_energy_left();
+                let energy_left_r
+                    = player.get_hero(HeroPos::R).get_energy_left();
+                let totals = Wheel::totals(rolls);
+                let target
+                    = if (1..=2).contains(&energy_left_l) {
+                        WheelKind::Square
+                    } else if (1..=2).contains(&energy_left_r) {
+                        WheelKind::Diamond
+                    } else {
+                        totals.max_kind()
+                    };
+                rolls.iter()
+                    .zip(locks.iter_mut())
+                    .for_each(|(wheel, lock)| {
+                        *lock = wheel.get_kind() == target;
+                    });
+            },
+            Self::Advisor => {
+                *locks
+                    = advisor::advise_locked(player, rolls, spins_left, &locked)
+                    .locks;
+            },
+            Self::Random => {
+                locks.iter_mut().for_each(|lock| { *lock = rng.gen(); });
+            },
+            Self::Idle => {
+                locks.iter_mut().for_each(|lock| { *lock = false; });
+            },
+        }
+        locks.iter_mut().zip(locked)
+            .for_each(|(lock, l)| { *lock = *lock || l; });
+    }
+}
+
+impl std::fmt::Display for Strategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        format!("{:?}", self).fmt(f)
+    }
+}
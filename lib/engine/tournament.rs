@@ -0,0 +1,239 @@
+use rand::{ SeedableRng, rngs::StdRng };
+use rayon::prelude::*;
+use crate::engine::{
+    batch::{ Progress, run_on },
+    game::{ Game, Winner },
+    hero::{ Hero, HeroKind },
+    player::Player,
+    rules::Rules,
+    strategy::Strategy,
+};
+
+/// Rating given to every entrant before any games are played.
+pub const INIT_RATING: f64 = 1500.0;
+
+/// Maximum rating change from a single game.
+pub const ELO_K: f64 = 16.0;
+
+/// A strategy playing a particular pair of heroes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Entrant {
+    pub strategy: Strategy,
+    pub hero_l: HeroKind,
+    pub hero_r: HeroKind,
+}
+
+impl Entrant {
+    pub fn new(strategy: Strategy, hero_l: HeroKind, hero_r: HeroKind) -> Self {
+        Self { strategy, hero_l, hero_r }
+    }
+
+    fn player(&self) -> Player {
+        Player::new(
+            &self.to_string(),
+            Hero::from_kind(self.hero_l),
+            Hero::from_kind(self.hero_r),
+        )
+    }
+}
+
+impl std::fmt::Display for Entrant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}/{}", self.strategy, self.hero_l, self.hero_r)
+    }
+}
+
+/// All ordered pairs of distinct heroes.
+pub fn all_loadouts() -> Vec<(HeroKind, HeroKind)> {
+    HeroKind::all().into_iter()
+        .flat_map(|l| {
+            HeroKind::all().into_iter()
+                .filter(move |r| *r != l)
+                .map(move |r| (l, r))
+        })
+        .collect()
+}
+
+/// Final results for a single entrant.
+#[derive(Copy, Clone, Debug)]
+pub struct Standing {
+    pub entrant: Entrant,
+    /// Elo rating.
+    pub rating: f64,
+    pub wins: usize,
+    pub losses: usize,
+    pub draws: usize,
+    /// Total number of turns over all games played.
+    pub turns: usize,
+}
+
+impl Standing {
+    fn new(entrant: Entrant) -> Self {
+        Self {
+            entrant,
+            rating: INIT_RATING,
+            wins: 0,
+            losses: 0,
+            draws: 0,
+            turns: 0,
+        }
+    }
+
+    pub fn games(&self) -> usize { self.wins + self.losses + self.draws }
+
+    /// Average number of turns per game played.
+    pub fn mean_turns(&self) -> f64 {
+        self.turns as f64 / self.games().max(1) as f64
+    }
+}
+
+/// Derive the seed for game number `index` from a master `seed`.
+pub fn game_seed(seed: u64, index: u64) -> u64 {
+    // splitmix64
+    let mut z: u64
+        = seed.wrapping_add(
+            index.wrapping_add(1).wrapping_mul(0x9e3779b97f4a7c15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// Expected score of a player rated `rating` against one rated `opp`.
+fn elo_expected(rating: f64, opp: f64) -> f64 {
+    1.0 / (1.0 + 10.0_f64.powf((opp - rating) / 400.0))
+}
+
+/// A round-robin between a set of entrants.
+///
+/// Every pair of entrants plays `games` games in each seating, so that any
+/// advantage from being Player 1 cancels out. Both seatings of the `n`th
+/// pairing are played with an RNG seeded from [`game_seed`]`(seed, n)`, so
+/// each seat sees the same random draws either way round. Games are played
+/// in parallel, but ratings are updated in order, so results don't depend on
+/// the number of threads.
+#[derive(Clone, Debug)]
+pub struct Tournament {
+    entrants: Vec<Entrant>,
+    games: usize,
+    seed: u64,
+    rules: Rules,
+    threads: Option<usize>,
+    progress: bool,
+}
+
+impl Tournament {
+    pub fn new(entrants: Vec<Entrant>, games: usize, seed: u64, rules: Rules)
+        -> Self
+    {
+        Self { entrants, games, seed, rules, threads: None, progress: false }
+    }
+
+    /// Play on `threads` threads instead of one per core.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads);
+        self
+    }
+
+    /// Report progress on stderr while playing.
+    pub fn progress(mut self, progress: bool) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Total number of games that will be played.
+    pub fn num_games(&self) -> usize {
+        let n = self.entrants.len();
+        n * n.saturating_sub(1) * self.games
+    }
+
+    /// Play a single game between `p1` and `p2`.
+    pub fn play(p1: &Entrant, p2: &Entrant, seed: u64, rules: Rules)
+        -> (Winner, usize)
+    {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut game = Game::new(p1.player(), p2.player());
+        game.set_rules(rules);
+        game.run_headless(p1.strategy, p2.strategy, &mut rng)
+    }
+
+    /// Every game to be played, as the indices of Player 1 and Player 2, in
+    /// order, with both seatings of a pair next to each other.
+    fn pairings(&self) -> Vec<(usize, usize)> {
+        let n = self.entrants.len();
+        let mut pairings: Vec<(usize, usize)> = Vec::new();
+        for _ in 0..self.games {
+            for i in 0..n {
+                for j in i + 1..n {
+                    pairings.push((i, j));
+                    pairings.push((j, i));
+                }
+            }
+        }
+        pairings
+    }
+
+    /// Play all games and return final standings, sorted from highest rating
+    /// to lowest.
+    pub fn run(&self) -> Result<Vec<Standing>, String> {
+        let pairings = self.pairings();
+        let progress = self.progress.then(|| Progress::new(pairings.len()));
+        let play = || -> Vec<(Winner, usize)> {
+            pairings.par_iter().enumerate()
+                .map(|(index, (a, b))| {
+                    // both seatings of a pair share a seed
+                    let seed = game_seed(self.seed, (index / 2) as u64);
+                    let result
+                        = Self::play(
+                            &self.entrants[*a],
+                            &self.entrants[*b],
+                            seed,
+                            self.rules,
+                        );
+                    if let Some(progress) = progress.as_ref() {
+                        progress.tick();
+                    }
+                    result
+                })
+                .collect()
+        };
+        let results = run_on(self.threads, play)?;
+        if let Some(progress) = progress.as_ref() { progress.finish(); }
+
+        let mut standings: Vec<Standing>
+            = self.entrants.iter().copied().map(Standing::new).collect();
+        for ((a, b), (winner, turns)) in pairings.into_iter().zip(results) {
+            Self::update(&mut standings, a, b, winner);
+            standings[a].turns += turns;
+            standings[b].turns += turns;
+        }
+        standings.sort_by(|l, r| r.rating.total_cmp(&l.rating));
+        Ok(standings)
+    }
+
+    fn update(standings: &mut [Standing], p1: usize, p2: usize, winner: Winner)
+    {
+        let score: f64
+            = match winner {
+                Winner::P1(_) => {
+                    standings[p1].wins += 1;
+                    standings[p2].losses += 1;
+                    1.0
+                },
+                Winner::P2(_) => {
+                    standings[p1].losses += 1;
+                    standings[p2].wins += 1;
+                    0.0
+                },
+                Winner::Draw(_) => {
+                    standings[p1].draws += 1;
+                    standings[p2].draws += 1;
+                    0.5
+                },
+            };
+        let r1 = standings[p1].rating;
+        let r2 = standings[p2].rating;
+        let delta: f64 = ELO_K * (score - elo_expected(r1, r2));
+        standings[p1].rating += delta;
+        standings[p2].rating -= delta;
+    }
+}
@@ -0,0 +1,166 @@
+use crate::engine::{
+    game::PlayerPos,
+    hero::{ HeroKind, MAX_LEVEL, MAX_EXP },
+    player::{ Player, HeroPos, MAX_CROWN, MAX_BULWARK },
+    rules::Rules,
+};
+
+/// A broken invariant of the game state.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Violation {
+    Crown(PlayerPos, u8),
+    Bulwark(PlayerPos, u8),
+    SameHeroes(PlayerPos, HeroKind),
+    Level(PlayerPos, HeroPos, HeroKind, u8),
+    Exp(PlayerPos, HeroPos, HeroKind, u8),
+    /// Energy beyond the rod length, given second.
+    Energy(PlayerPos, HeroPos, HeroKind, u8, u8),
+    /// A hero about to act should have an empty rod, unless energy carries
+    /// over.
+    ActEnergy(PlayerPos, HeroPos, HeroKind, u8),
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Crown(player, crown) => {
+                write!(f, "{:?}: crown {} exceeds the maximum of {}",
+                    player, crown, MAX_CROWN)
+            },
+            Self::Bulwark(player, bulwark) => {
+                write!(f, "{:?}: bulwark {} exceeds the maximum of {}",
+                    player, bulwark, MAX_BULWARK)
+            },
+            Self::SameHeroes(player, kind) => {
+                write!(f, "{:?}: both heroes are {}", player, kind)
+            },
+            Self::Level(player, hero, kind, level) => {
+                write!(f, "{:?} {:?} {}: level {} exceeds the maximum of {}",
+                    player, hero, kind, level, MAX_LEVEL)
+            },
+            Self::Exp(player, hero, kind, exp) => {
+                write!(f, "{:?} {:?} {}: EXP {} must be less than {}",
+                    player, hero, kind, exp, MAX_EXP)
+            },
+            Self::Energy(player, hero, kind, energy, rod_len) => {
+                write!(f,
+                    "{:?} {:?} {}: energy {} exceeds the rod length of {}",
+                    player, hero, kind, energy, rod_len)
+            },
+            Self::ActEnergy(player, hero, kind, energy) => {
+                write!(f, "{:?} {:?} {}: about to act with {} energy",
+                    player, hero, kind, energy)
+            },
+        }
+    }
+}
+
+/// Find every invariant broken by the state of `player` under `rules`.
+pub fn validate_player(pos: PlayerPos, player: &Player, rules: &Rules)
+    -> Vec<Violation>
+{
+    let mut violations: Vec<Violation> = Vec::new();
+    if player.get_crown() > MAX_CROWN {
+        violations.push(Violation::Crown(pos, player.get_crown()));
+    }
+    if player.get_bulwark() > MAX_BULWARK {
+        violations.push(Violation::Bulwark(pos, player.get_bulwark()));
+    }
+    let kind_l = player.get_hero(HeroPos::L).get_kind();
+    if kind_l == player.get_hero(HeroPos::R).get_kind() {
+        violations.push(Violation::SameHeroes(pos, kind_l));
+    }
+    for hpos in [HeroPos::L, HeroPos::R] {
+        let hero = player.get_hero(hpos);
+        let kind = hero.get_kind();
+        if hero.get_level() > MAX_LEVEL {
+            violations.push(
+                Violation::Level(pos, hpos, kind, hero.get_level()));
+            // nothing else is defined above the maximum level
+            continue;
+        }
+        if hero.get_exp() >= MAX_EXP {
+            violations.push(Violation::Exp(pos, hpos, kind, hero.get_exp()));
+        }
+        if hero.get_energy() > hero.get_rod_len() {
+            violations.push(Violation::Energy(
+                pos, hpos, kind, hero.get_energy(), hero.get_rod_len()));
+        }
+        if hero.get_act() && hero.get_energy() > 0 && !rules.carry_over {
+            violations.push(
+                Violation::ActEnergy(pos, hpos, kind, hero.get_energy()));
+        }
+    }
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::{
+        game::Game,
+        hero::Hero,
+    };
+    use super::*;
+
+    fn player(hero_l: HeroKind, hero_r: HeroKind) -> Player {
+        Player::new("", Hero::from_kind(hero_l), Hero::from_kind(hero_r))
+    }
+
+    #[test]
+    fn valid_players() {
+        let rules = Rules::default();
+        let player = player(HeroKind::Warrior, HeroKind::Mage);
+        assert!(validate_player(PlayerPos::P1, &player, &rules).is_empty());
+        let game = Game::new(player.clone(), player);
+        assert_eq!(game.validate(), Ok(()));
+    }
+
+    #[test]
+    fn same_heroes() {
+        let player = player(HeroKind::Priest, HeroKind::Priest);
+        assert_eq!(
+            validate_player(PlayerPos::P2, &player, &Rules::default()),
+            vec![Violation::SameHeroes(PlayerPos::P2, HeroKind::Priest)]);
+    }
+
+    #[test]
+    fn hero_violations() {
+        use HeroKind::*;
+        let rules = Rules::default();
+        let mut player = player(Archer, Engineer);
+        let archer = player.get_hero_mut(HeroPos::L);
+        *archer.get_exp_mut() = MAX_EXP;
+        *archer.get_energy_mut() = 5;
+        let engineer = player.get_hero_mut(HeroPos::R);
+        engineer.set_energy(1).unwrap();
+        engineer.set_act(true);
+        assert_eq!(validate_player(PlayerPos::P1, &player, &rules), vec![
+            Violation::Exp(PlayerPos::P1, HeroPos::L, Archer, MAX_EXP),
+            Violation::Energy(PlayerPos::P1, HeroPos::L, Archer, 5, 4),
+            Violation::ActEnergy(PlayerPos::P1, HeroPos::R, Engineer, 1),
+        ]);
+
+        // leftover energy is allowed while acting if it carries over
+        let carry_over = Rules { carry_over: true, ..rules };
+        assert_eq!(validate_player(PlayerPos::P1, &player, &carry_over).len(),
+            2);
+
+        // nothing else is checked above the maximum level
+        *player.get_hero_mut(HeroPos::L).get_level_mut() = MAX_LEVEL + 1;
+        assert_eq!(validate_player(PlayerPos::P1, &player, &carry_over), vec![
+            Violation::Level(PlayerPos::P1, HeroPos::L, Archer, MAX_LEVEL + 1),
+        ]);
+    }
+
+    #[test]
+    fn game_collects_both_players() {
+        let p1 = player(HeroKind::Mage, HeroKind::Mage);
+        let p2 = player(HeroKind::Archer, HeroKind::Archer);
+        let violations = Game::new(p1, p2).validate().unwrap_err();
+        assert_eq!(violations, vec![
+            Violation::SameHeroes(PlayerPos::P1, HeroKind::Mage),
+            Violation::SameHeroes(PlayerPos::P2, HeroKind::Archer),
+        ]);
+        assert_eq!(violations[0].to_string(), "P1: both heroes are Mage");
+    }
+}
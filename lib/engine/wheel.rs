@@ -1,11 +1,11 @@
-use rand::prelude::{ thread_rng, Rng };
+use rand::Rng;
 
 /// Description of a single wheel (with a particular panel facing up).
 ///
 /// Wheel panels have a quantity (1-3, inclusive) of each symbol. Squares and
 /// Diamonds have an extra boolean flag controlling whether they count toward
 /// hero EXP.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Wheel {
     Square(u8, bool), 
     Diamond(u8, bool), 
@@ -44,8 +44,50 @@ impl std::fmt::Display for Wheel {
     }
 }
 
+impl std::str::FromStr for Wheel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (symbols, exp)
+            = match s.strip_suffix('*') {
+                Some(symbols) => (symbols, true),
+                None => (s, false),
+            };
+        let n = symbols.len() as u8;
+        let invalid = || format!("invalid wheel '{}'", s);
+        let symbol = symbols.chars().next().ok_or_else(invalid)?;
+        if !(1..=3).contains(&n) || symbols.chars().any(|c| c != symbol) {
+            return Err(invalid());
+        }
+        match (symbol, exp) {
+            ('S', b) => Ok(Self::Square(n, b)),
+            ('D', b) => Ok(Self::Diamond(n, b)),
+            ('H', false) => Ok(Self::Hammer(n)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
 pub type Rolls = [Wheel; 5];
 
+/// Parse a whitespace-separated series of five wheels, e.g. `S DD* HH S SS*`.
+pub fn parse_rolls(s: &str) -> Result<Rolls, String> {
+    let wheels: Vec<Wheel>
+        = s.split_whitespace()
+        .map(|w| w.parse::<Wheel>())
+        .collect::<Result<_, _>>()?;
+    wheels.try_into()
+        .map_err(|_| format!("expected 5 wheels in '{}'", s.trim()))
+}
+
+/// Render a series of wheels in the format read by [`parse_rolls`].
+pub fn fmt_rolls(rolls: &Rolls) -> String {
+    rolls.iter().map(|w| w.to_string()).collect::<Vec<_>>().join(" ")
+}
+
+/// Number of faces on each wheel.
+pub const NUM_FACES: u8 = 8;
+
 impl Wheel {
     // W0: S, D, S, S+, D, H, DD+, H
     fn w0_sides(face: u8) -> Self {
@@ -137,6 +179,22 @@ impl Wheel {
         }
     }
 
+    /// Get the panel on face `face` (0-7, inclusive) of wheel `wheel` (0-4,
+    /// inclusive).
+    pub fn sides(wheel: usize, face: u8) -> Self {
+        match wheel {
+            0 => Self::w0_sides(face),
+            1 => Self::w1_sides(face),
+            2 => Self::w2_sides(face),
+            3 => Self::w3_sides(face),
+            4 => Self::w4_sides(face),
+            _ => {
+                eprintln!("Wheel::sides");
+                unreachable!()
+            },
+        }
+    }
+
     /// Generate a series of wheel states.
     pub fn gen_rolls<R>(rng: &mut R) -> Rolls
     where R: Rng + ?Sized
@@ -202,7 +260,7 @@ impl Wheel {
 }
 
 /// Total counts from a series of rolls.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Default)]
 pub struct RollTotals {
     /// Total number of squares.
     pub squares: u8,
@@ -226,15 +284,7 @@ impl RollTotals {
         .into_iter()
         .max_by(|l, r| l.0.cmp(&r.0))
         .map(|(_, kind)| kind)
-        .unwrap_or_else(|| {
-            let mut rng = thread_rng();
-            match rng.gen_range(0..=2_u8) {
-                0 => WheelKind::Square,
-                1 => WheelKind::Diamond,
-                2 => WheelKind::Hammer,
-                _ => unreachable!(),
-            }
-        })
+        .expect("there should be three kinds to choose from")
     }
 }
 
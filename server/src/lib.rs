@@ -0,0 +1,378 @@
+//! A local HTTP/JSON API for games of Wheels, so that tools and bots can
+//! drive a [`Game`] without linking Rust.
+//!
+//! Matches are held in memory by ID. Every request and response body is JSON;
+//! players are numbered 1 and 2.
+//!
+//! - `POST /matches` creates a match from
+//!   `{"players": [{"name": "Ann", "heroes": ["Warrior", "Mage"]}, ...],
+//!   "cpu": 2, "seed": 42}`, where `cpu` (the seat played by the computer)
+//!   and `seed` are optional. Returns its state.
+//! - `GET /matches/<id>` returns the board, each player's current spin, the
+//!   players whose locks are awaited and the winner, if any.
+//! - `POST /matches/<id>/locks` submits `{"player": 1, "locks": [true, false,
+//!   false, true, false]}` for the current spin, then spins the unlocked
+//!   wheels again. The turn is played once every player has used all of
+//!   their re-spins. Returns the new state.
+//! - `GET /matches/<id>/log` returns the final rolls and the actions of every
+//!   turn played so far.
+
+use std::{
+    collections::HashMap,
+    time::{ SystemTime, UNIX_EPOCH },
+};
+use rand::{ SeedableRng, rngs::StdRng };
+use serde::Deserialize;
+use serde_json::{ Value, json };
+use tiny_http::{ Header, Request, Response, Server };
+use wheels::engine::{
+    game::{ Game, PlayerPos, Winner },
+    hero::{ Hero, HeroKind },
+    json::{ player_json, player_num, rolls_json, winner_json },
+    player::Player,
+    strategy::Strategy,
+    wheel::{ Wheel, Rolls },
+};
+
+/// Status code and body of a response, or status code and error message.
+type Reply = Result<(u16, Value), (u16, String)>;
+
+fn bad_request(msg: String) -> (u16, String) { (400, msg) }
+
+fn player_pos(num: u8) -> Result<PlayerPos, String> {
+    match num {
+        1 => Ok(PlayerPos::P1),
+        2 => Ok(PlayerPos::P2),
+        _ => Err(format!("player must be 1 or 2, got {}", num)),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct NewPlayer {
+    name: String,
+    heroes: [String; 2],
+}
+
+impl NewPlayer {
+    fn to_player(&self) -> Result<Player, String> {
+        let kind = |name: &str| -> Result<HeroKind, String> {
+            HeroKind::from_name(name)
+                .ok_or_else(|| format!("unknown hero '{}'", name))
+        };
+        let hero_l = kind(&self.heroes[0])?;
+        let hero_r = kind(&self.heroes[1])?;
+        if hero_l == hero_r {
+            return Err(format!("{} can't have two {}s", self.name, hero_l));
+        }
+        Ok(Player::new(
+            &self.name, Hero::from_kind(hero_l), Hero::from_kind(hero_r)))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct NewMatch {
+    players: [NewPlayer; 2],
+    cpu: Option<u8>,
+    seed: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Locks {
+    player: u8,
+    locks: [bool; 5],
+}
+
+/// A player's rolls and locks part way through a turn.
+#[derive(Copy, Clone, Debug)]
+struct Spin {
+    rolls: Rolls,
+    locks: [bool; 5],
+    spins_left: usize,
+}
+
+/// A turn that has been played.
+#[derive(Clone, Debug)]
+struct LoggedTurn {
+    turn: usize,
+    rolls: [Option<Rolls>; 2],
+    actions: Vec<String>,
+}
+
+/// A match being played through the API.
+#[derive(Clone, Debug)]
+pub struct Session {
+    game: Game,
+    rng: StdRng,
+    cpu: Option<PlayerPos>,
+    /// Each player's spin on the current turn, if their rolls resolve on it.
+    spins: [Option<Spin>; 2],
+    log: Vec<LoggedTurn>,
+    winner: Option<Winner>,
+}
+
+impl Session {
+    fn new(request: &NewMatch) -> Result<Self, String> {
+        let p1 = request.players[0].to_player()?;
+        let p2 = request.players[1].to_player()?;
+        let cpu = request.cpu.map(player_pos).transpose()?;
+        let seed
+            = request.seed
+            .unwrap_or_else(|| {
+                SystemTime::now().duration_since(UNIX_EPOCH)
+                    .map_or(0, |d| d.as_nanos() as u64)
+            });
+        let mut session = Self {
+            game: Game::new(p1, p2),
+            rng: StdRng::seed_from_u64(seed),
+            cpu,
+            spins: [None, None],
+            log: Vec::new(),
+            winner: None,
+        };
+        session.start_turn();
+        Ok(session)
+    }
+
+    fn index(pos: PlayerPos) -> usize { player_num(pos) as usize - 1 }
+
+    /// Spin for every player whose rolls resolve on the next turn, other than
+    /// the computer.
+    fn start_turn(&mut self) {
+        let turn = self.game.get_turn() + 1;
+        for pos in [PlayerPos::P1, PlayerPos::P2] {
+            let active = self.game.get_rules().turn_mode.is_active(pos, turn);
+            self.spins[Self::index(pos)]
+                = (active && self.cpu != Some(pos)).then(|| Spin {
+                    rolls: Wheel::gen_rolls(&mut self.rng),
+                    locks: [false; 5],
+                    spins_left: self.game.get_player(pos).get_handicap()
+                        .get_respins(),
+                });
+        }
+    }
+
+    /// Players whose locks are awaited on the current spin.
+    fn waiting_for(&self) -> Vec<PlayerPos> {
+        if self.winner.is_some() { return Vec::new(); }
+        [PlayerPos::P1, PlayerPos::P2].into_iter()
+            .filter(|pos| {
+                self.spins[Self::index(*pos)]
+                    .is_some_and(|spin| spin.spins_left > 0)
+            })
+            .collect()
+    }
+
+    /// Lock the wheels of `player` and spin the rest again, then play the
+    /// turn if no more locks are awaited.
+    fn submit_locks(&mut self, player: PlayerPos, locks: [bool; 5])
+        -> Result<(), String>
+    {
+        if self.winner.is_some() {
+            return Err("the game is over".to_string());
+        }
+        if self.cpu == Some(player) {
+            return Err(format!("player {} is the computer",
+                player_num(player)));
+        }
+        let permanent = self.game.get_rules().permanent_locks;
+        let spin
+            = self.spins[Self::index(player)].as_mut()
+            .filter(|spin| spin.spins_left > 0)
+            .ok_or_else(|| {
+                format!("no re-spins left for player {} this turn",
+                    player_num(player))
+            })?;
+        if permanent && spin.locks.iter().zip(locks).any(|(&l, k)| l && !k) {
+            return Err("locked wheels can't be unlocked".to_string());
+        }
+        spin.locks = locks;
+        Wheel::gen_rolls_locked(&mut spin.rolls, &spin.locks, &mut self.rng);
+        spin.spins_left -= 1;
+        if self.waiting_for().is_empty() { self.play_turn(); }
+        Ok(())
+    }
+
+    fn play_turn(&mut self) {
+        let turn = self.game.get_turn() + 1;
+        let rolls: [Option<Rolls>; 2]
+            = [PlayerPos::P1, PlayerPos::P2].map(|pos| {
+                let active
+                    = self.game.get_rules().turn_mode.is_active(pos, turn);
+                if self.cpu == Some(pos) {
+                    active.then(|| {
+                        self.game.get_rolls_strategy(
+                            pos, Strategy::Classic, &mut self.rng)
+                    })
+                } else {
+                    self.spins[Self::index(pos)].map(|spin| spin.rolls)
+                }
+            });
+        let (winner, log)
+            = self.game.do_turn_partial_rng(
+                rolls[0].as_ref(), rolls[1].as_ref(), &mut self.rng);
+        let actions
+            = log.iter().map(|action| action.msg(&self.game)).collect();
+        self.log.push(LoggedTurn { turn, rolls, actions });
+        self.winner = winner;
+        match winner {
+            Some(_) => { self.spins = [None, None]; },
+            None => { self.start_turn(); },
+        }
+    }
+
+    fn player_json(&self, pos: PlayerPos) -> Value {
+        let spin
+            = self.spins[Self::index(pos)].map(|spin| {
+                json!({
+                    "rolls": rolls_json(&spin.rolls),
+                    "locks": spin.locks,
+                    "spinsLeft": spin.spins_left,
+                })
+            });
+        let mut player = player_json(self.game.get_player(pos));
+        player["cpu"] = json!(self.cpu == Some(pos));
+        player["spin"] = json!(spin);
+        player
+    }
+
+    fn state_json(&self, id: u64) -> Value {
+        json!({
+            "id": id,
+            "turn": self.game.get_turn(),
+            "players": [
+                self.player_json(PlayerPos::P1),
+                self.player_json(PlayerPos::P2),
+            ],
+            "waitingFor": self.waiting_for().into_iter()
+                .map(player_num).collect::<Vec<u8>>(),
+            "winner": self.winner.map(winner_json),
+        })
+    }
+
+    fn log_json(&self, id: u64) -> Value {
+        let turns: Vec<Value>
+            = self.log.iter()
+            .map(|turn| {
+                json!({
+                    "turn": turn.turn,
+                    "rolls": turn.rolls.map(|r| r.as_ref().map(rolls_json)),
+                    "actions": turn.actions,
+                })
+            })
+            .collect();
+        json!({
+            "id": id,
+            "turns": turns,
+            "winner": self.winner.map(winner_json),
+        })
+    }
+}
+
+/// Every match being played, by ID.
+#[derive(Clone, Debug, Default)]
+pub struct Sessions {
+    sessions: HashMap<u64, Session>,
+    next_id: u64,
+}
+
+impl Sessions {
+    pub fn new() -> Self { Self::default() }
+
+    /// Respond to a request for `url` with `body`.
+    pub fn handle(&mut self, method: &str, url: &str, body: &str) -> Reply {
+        let path = url.split('?').next().unwrap_or_default();
+        let parts: Vec<&str>
+            = path.split('/').filter(|part| !part.is_empty()).collect();
+        match (method, parts.as_slice()) {
+            ("POST", ["matches"]) => self.create(body),
+            ("GET", ["matches", id]) => {
+                let (id, session) = self.get(id)?;
+                Ok((200, session.state_json(id)))
+            },
+            ("POST", ["matches", id, "locks"]) => {
+                let request: Locks
+                    = serde_json::from_str(body)
+                    .map_err(|e| bad_request(e.to_string()))?;
+                let pos = player_pos(request.player).map_err(bad_request)?;
+                let (id, session) = self.get_mut(id)?;
+                session.submit_locks(pos, request.locks)
+                    .map_err(|e| (409, e))?;
+                Ok((200, session.state_json(id)))
+            },
+            ("GET", ["matches", id, "log"]) => {
+                let (id, session) = self.get(id)?;
+                Ok((200, session.log_json(id)))
+            },
+            (_, ["matches"]) | (_, ["matches", _])
+                | (_, ["matches", _, "locks" | "log"])
+                => Err((405, format!("{} not allowed on {}", method, path))),
+            _ => Err((404, format!("no such endpoint {}", path))),
+        }
+    }
+
+    fn create(&mut self, body: &str) -> Reply {
+        let request: NewMatch
+            = serde_json::from_str(body)
+            .map_err(|e| bad_request(e.to_string()))?;
+        let session = Session::new(&request).map_err(bad_request)?;
+        self.next_id += 1;
+        let id = self.next_id;
+        let state = session.state_json(id);
+        self.sessions.insert(id, session);
+        Ok((201, state))
+    }
+
+    fn parse_id(id: &str) -> Result<u64, (u16, String)> {
+        id.parse().map_err(|_| (404, format!("no match with ID '{}'", id)))
+    }
+
+    fn get(&self, id: &str) -> Result<(u64, &Session), (u16, String)> {
+        let id = Self::parse_id(id)?;
+        self.sessions.get(&id)
+            .map(|session| (id, session))
+            .ok_or_else(|| (404, format!("no match with ID {}", id)))
+    }
+
+    fn get_mut(&mut self, id: &str)
+        -> Result<(u64, &mut Session), (u16, String)>
+    {
+        let id = Self::parse_id(id)?;
+        self.sessions.get_mut(&id)
+            .map(|session| (id, session))
+            .ok_or_else(|| (404, format!("no match with ID {}", id)))
+    }
+
+    fn respond(&mut self, mut request: Request) {
+        let mut body = String::new();
+        let reply
+            = match request.as_reader().read_to_string(&mut body) {
+                Ok(_) => {
+                    let method = request.method().as_str().to_string();
+                    self.handle(&method, request.url(), &body)
+                },
+                Err(e) => {
+                    Err(bad_request(format!("error reading body: {}", e)))
+                },
+            };
+        let (status, value)
+            = reply
+            .unwrap_or_else(|(status, e)| (status, json!({ "error": e })));
+        let content_type
+            = Header::from_bytes("Content-Type", "application/json")
+            .expect("header should be valid");
+        let response
+            = Response::from_string(value.to_string())
+            .with_status_code(status)
+            .with_header(content_type);
+        if let Err(e) = request.respond(response) {
+            eprintln!("error sending response: {}", e);
+        }
+    }
+
+    /// Answer requests to `server` until it shuts down.
+    pub fn serve(&mut self, server: &Server) {
+        for request in server.incoming_requests() {
+            self.respond(request);
+        }
+    }
+}
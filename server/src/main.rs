@@ -0,0 +1,53 @@
+use std::process;
+use tiny_http::Server;
+use wheels_server::Sessions;
+
+const USAGE: &str = "\
+usage: wheels-server [options]
+
+Serve games of Wheels over a local HTTP/JSON API, holding matches in memory
+until the server stops.
+
+endpoints:
+    POST /matches               create a match from {\"players\": [{\"name\":
+                                ..., \"heroes\": [L, R]}, ...], \"cpu\": 1 or 2,
+                                \"seed\": S}; cpu and seed are optional
+    GET  /matches/ID            the board and each player's current spin
+    POST /matches/ID/locks      lock {\"player\": P, \"locks\": [5 bools]} and
+                                spin the other wheels again; the turn is
+                                played once every re-spin is used
+    GET  /matches/ID/log        rolls and actions of every turn played
+
+options:
+    --addr ADDR     address to listen on [default: 127.0.0.1:8080]
+    -h, --help      print this message";
+
+fn fail(msg: &str) -> ! {
+    eprintln!("error: {}\n\n{}", msg, USAGE);
+    process::exit(1);
+}
+
+fn main() {
+    let mut addr = "127.0.0.1:8080".to_string();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--addr" => {
+                addr = args.next()
+                    .unwrap_or_else(|| fail("missing value for --addr"));
+            },
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            },
+            _ => { fail(&format!("unknown argument '{}'", arg)); },
+        }
+    }
+
+    let server
+        = Server::http(&addr)
+        .unwrap_or_else(|e| fail(&format!("can't listen on {}: {}", addr, e)));
+    eprintln!("listening on http://{}", addr);
+    Sessions::new().serve(&server);
+}
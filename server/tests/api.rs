@@ -0,0 +1,187 @@
+//! Drive the API over a real connection to localhost.
+
+use std::{
+    io::{ Read, Write },
+    net::{ SocketAddr, TcpStream },
+    thread,
+};
+use serde_json::{ Value, json };
+use tiny_http::Server;
+use wheels_server::Sessions;
+
+/// Start a server on a free port, returning its address.
+fn start() -> SocketAddr {
+    let server = Server::http("127.0.0.1:0").unwrap();
+    let addr = server.server_addr().to_ip().unwrap();
+    thread::spawn(move || Sessions::new().serve(&server));
+    addr
+}
+
+fn request(addr: SocketAddr, method: &str, path: &str, body: Option<Value>)
+    -> (u16, Value)
+{
+    let body = body.map_or_else(String::new, |body| body.to_string());
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(
+        stream,
+        "{} {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\
+        Content-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+        method, path, body.len(), body,
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+    (status, serde_json::from_str(body).unwrap())
+}
+
+fn new_match(cpu: Option<u8>, seed: u64) -> Value {
+    json!({
+        "players": [
+            { "name": "Ann", "heroes": ["Warrior", "Mage"] },
+            { "name": "Bob", "heroes": ["Priest", "Archer"] },
+        ],
+        "cpu": cpu,
+        "seed": seed,
+    })
+}
+
+/// Lock every square of `player` in `state`.
+fn lock_squares(state: &Value, player: usize) -> Value {
+    let rolls = state["players"][player - 1]["spin"]["rolls"].as_array();
+    let locks: Vec<bool>
+        = rolls.unwrap().iter()
+        .map(|panel| panel.as_str().unwrap().starts_with('S'))
+        .collect();
+    json!({ "player": player, "locks": locks })
+}
+
+/// Play a match against the computer to the end, returning its log.
+fn play_vs_cpu(addr: SocketAddr, seed: u64) -> Value {
+    let (status, mut state)
+        = request(addr, "POST", "/matches", Some(new_match(Some(2), seed)));
+    assert_eq!(status, 201);
+    let path = format!("/matches/{}", state["id"]);
+    assert_eq!(state["players"][1]["cpu"], true);
+    assert_eq!(state["players"][1]["spin"], Value::Null);
+    while state["winner"].is_null() {
+        assert_eq!(state["waitingFor"], json!([1]));
+        let locks = lock_squares(&state, 1);
+        let (status, next)
+            = request(addr, "POST", &format!("{}/locks", path), Some(locks));
+        assert_eq!(status, 200, "{}", next);
+        state = next;
+        assert!(state["turn"].as_u64().unwrap() < 1000);
+    }
+    assert_eq!(state["waitingFor"], json!([]));
+    let (status, log) = request(addr, "GET", &format!("{}/log", path), None);
+    assert_eq!(status, 200);
+    assert_eq!(log["winner"], state["winner"]);
+    let turns = log["turns"].as_array().unwrap();
+    assert_eq!(turns.len() as u64, state["turn"].as_u64().unwrap());
+    for (k, turn) in turns.iter().enumerate() {
+        assert_eq!(turn["turn"], k + 1);
+        assert_eq!(turn["rolls"].as_array().unwrap().len(), 2);
+        assert!(turn["actions"].is_array());
+    }
+    let (status, error)
+        = request(addr, "POST", &format!("{}/locks", path),
+            Some(json!({ "player": 1, "locks": ([false; 5]) })));
+    assert_eq!(status, 409);
+    assert_eq!(error["error"], "the game is over");
+    log
+}
+
+#[test]
+fn match_against_cpu() {
+    let addr = start();
+    let log = play_vs_cpu(addr, 42);
+    // the same seed and locks play out the same way
+    let mut again = play_vs_cpu(addr, 42);
+    assert_ne!(again["id"], log["id"]);
+    again["id"] = log["id"].clone();
+    assert_eq!(again, log);
+}
+
+#[test]
+fn match_between_two_players() {
+    let addr = start();
+    let (status, state)
+        = request(addr, "POST", "/matches", Some(new_match(None, 7)));
+    assert_eq!(status, 201);
+    let path = format!("/matches/{}", state["id"]);
+    let locks = format!("{}/locks", path);
+    let lock = |state: &Value, player: usize| {
+        request(addr, "POST", &locks, Some(lock_squares(state, player)))
+    };
+    assert_eq!(state["waitingFor"], json!([1, 2]));
+    assert_eq!(state["players"][0]["spin"]["spinsLeft"], 2);
+
+    // the turn waits for both players to use their re-spins
+    let (_, state) = lock(&state, 1);
+    assert_eq!(state["players"][0]["spin"]["spinsLeft"], 1);
+    let (_, state) = lock(&state, 1);
+    assert_eq!(state["waitingFor"], json!([2]));
+    let (status, error) = lock(&state, 1);
+    assert_eq!(status, 409);
+    assert_eq!(error["error"], "no re-spins left for player 1 this turn");
+    let (_, state) = lock(&state, 2);
+    assert_eq!(state["turn"], 0);
+    let (_, state) = lock(&state, 2);
+    assert_eq!(state["turn"], 1);
+    assert_eq!(state["waitingFor"], json!([1, 2]));
+
+    let (status, fetched) = request(addr, "GET", &path, None);
+    assert_eq!(status, 200);
+    assert_eq!(fetched, state);
+    let (_, log) = request(addr, "GET", &format!("{}/log", path), None);
+    assert_eq!(log["turns"].as_array().unwrap().len(), 1);
+    assert_eq!(log["winner"], Value::Null);
+}
+
+#[test]
+fn bad_requests() {
+    let addr = start();
+    let mut bad_hero = new_match(Some(2), 1);
+    bad_hero["players"][0]["heroes"][1] = json!("Bard");
+    let (status, error) = request(addr, "POST", "/matches", Some(bad_hero));
+    assert_eq!(status, 400);
+    assert_eq!(error["error"], "unknown hero 'Bard'");
+    let mut same_heroes = new_match(Some(2), 1);
+    same_heroes["players"][1]["heroes"] = json!(["Mage", "Mage"]);
+    let (status, error) = request(addr, "POST", "/matches", Some(same_heroes));
+    assert_eq!(status, 400);
+    assert_eq!(error["error"], "Bob can't have two Mages");
+    let (status, _)
+        = request(addr, "POST", "/matches", Some(json!({ "players": [] })));
+    assert_eq!(status, 400);
+
+    let (status, error) = request(addr, "GET", "/matches/1", None);
+    assert_eq!(status, 404);
+    assert_eq!(error["error"], "no match with ID 1");
+    let (status, _) = request(addr, "GET", "/matches/one", None);
+    assert_eq!(status, 404);
+    let (status, _) = request(addr, "GET", "/players", None);
+    assert_eq!(status, 404);
+    let (status, _) = request(addr, "DELETE", "/matches", None);
+    assert_eq!(status, 405);
+
+    let (_, state)
+        = request(addr, "POST", "/matches", Some(new_match(Some(2), 1)));
+    let locks = format!("/matches/{}/locks", state["id"]);
+    let (status, error)
+        = request(addr, "POST", &locks,
+            Some(json!({ "player": 2, "locks": ([false; 5]) })));
+    assert_eq!(status, 409);
+    assert_eq!(error["error"], "player 2 is the computer");
+    let (status, error)
+        = request(addr, "POST", &locks,
+            Some(json!({ "player": 3, "locks": ([false; 5]) })));
+    assert_eq!(status, 400);
+    assert_eq!(error["error"], "player must be 1 or 2, got 3");
+    let (status, _)
+        = request(addr, "POST", &locks,
+            Some(json!({ "player": 1, "locks": ([false; 4]) })));
+    assert_eq!(status, 400);
+}
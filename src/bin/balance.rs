@@ -0,0 +1,59 @@
+use std::process;
+use wheels::engine::{
+    balance::{ BalanceSheet, LockPolicy },
+    handicap::RESPINS,
+};
+
+const USAGE: &str = "\
+usage: balance [options]
+
+Compute, for every hero, level and side, the expected number of turns to act
+and the expected output per turn from panels alone, as a table that can be
+compared with `diff` across versions of the stat tables.
+
+options:
+    --policy P      lock policy, either `idle` (never lock) or `feed` (lock
+                    every panel feeding the hero's side) [default: feed]
+    --respins N     re-spins per turn [default: 2]
+    --carry-over    excess energy carries over to the next rod
+    -h, --help      print this message";
+
+fn fail(msg: &str) -> ! {
+    eprintln!("error: {}\n\n{}", msg, USAGE);
+    process::exit(1);
+}
+
+fn main() {
+    let mut policy = LockPolicy::Feed;
+    let mut respins: usize = RESPINS;
+    let mut carry_over = false;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || -> String {
+            args.next()
+                .unwrap_or_else(|| fail(&format!("missing value for {}", arg)))
+        };
+        match arg.as_str() {
+            "--policy" => {
+                let name = value();
+                policy = LockPolicy::from_name(&name)
+                    .unwrap_or_else(|| {
+                        fail(&format!("unknown lock policy '{}'", name))
+                    });
+            },
+            "--respins" => {
+                respins = value().parse()
+                    .unwrap_or_else(|_| fail("invalid number of re-spins"));
+            },
+            "--carry-over" => { carry_over = true; },
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            },
+            _ => { fail(&format!("unknown argument '{}'", arg)); },
+        }
+    }
+
+    print!("{}", BalanceSheet::new(policy, respins, carry_over));
+}
@@ -0,0 +1,95 @@
+use std::process;
+use wheels::engine::{
+    advisor::fmt_locks,
+    blunder::{ self, BLUNDER_THRESHOLD },
+    game::PlayerPos,
+    replay::Replay,
+    wheel::fmt_rolls,
+};
+
+const USAGE: &str = "\
+usage: blunders <replay> [options]
+
+Review each lock decision recorded in a replay (see `main --record`) and flag
+those that lost significant expected value.
+
+options:
+    --threshold X   flag decisions losing more than X in expected score
+                    [default: 0.5]
+    --player P      review only player P (P1 or P2) [default: all humans]
+    -h, --help      print this message";
+
+fn fail(msg: &str) -> ! {
+    eprintln!("error: {}\n\n{}", msg, USAGE);
+    process::exit(1);
+}
+
+fn main() {
+    let mut path: Option<String> = None;
+    let mut threshold: f64 = BLUNDER_THRESHOLD;
+    let mut player: Option<PlayerPos> = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || -> String {
+            args.next()
+                .unwrap_or_else(|| fail(&format!("missing value for {}", arg)))
+        };
+        match arg.as_str() {
+            "--threshold" => {
+                threshold = value().parse()
+                    .unwrap_or_else(|_| fail("invalid threshold"));
+            },
+            "--player" => {
+                player = match value().as_str() {
+                    "P1" | "p1" | "1" => Some(PlayerPos::P1),
+                    "P2" | "p2" | "2" => Some(PlayerPos::P2),
+                    _ => fail("invalid player"),
+                };
+            },
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            },
+            _ if path.is_none() && !arg.starts_with('-') => {
+                path = Some(arg);
+            },
+            _ => { fail(&format!("unknown argument '{}'", arg)); },
+        }
+    }
+    let path = path.unwrap_or_else(|| fail("missing replay file"));
+    let replay = Replay::load(&path).unwrap_or_else(|e| fail(&e));
+
+    let players: Vec<PlayerPos>
+        = match player {
+            Some(pos) => vec![pos],
+            None => {
+                [PlayerPos::P1, PlayerPos::P2].into_iter()
+                    .filter(|pos| !replay.get_player(*pos).cpu)
+                    .collect()
+            },
+        };
+    for pos in players {
+        let name = &replay.get_player(pos).name;
+        let review = blunder::review(&replay, pos, threshold);
+        println!("{:?} ({}): {} decisions reviewed, {} flagged, \
+            {:.2} expected score lost in total",
+            pos,
+            name,
+            review.reviewed,
+            review.blunders.len(),
+            review.total_loss,
+        );
+        for b in review.blunders.iter() {
+            println!();
+            println!("  turn {}, {} spin(s) left: {}",
+                b.turn, b.spin.spins_left, fmt_rolls(&b.spin.rolls));
+            println!("    chose  lock {:<10} score {:.2}",
+                fmt_locks(&b.spin.locks), b.chosen.score);
+            println!("    better lock {:<10} score {:.2} (-{:.2})",
+                fmt_locks(&b.best.locks), b.best.outlook.score, b.loss());
+            println!("    {}", b.best.reason);
+        }
+        println!();
+    }
+}
@@ -0,0 +1,76 @@
+use std::process;
+use rand::{ SeedableRng, rngs::StdRng };
+use wheels::engine::{
+    fairness::{ Fairness, Luck },
+    game::PlayerPos,
+    replay::Replay,
+};
+
+const USAGE: &str = "\
+usage: fairness [replay...] [options]
+
+Count the panels landed on each wheel, either by fresh spins in replays (see
+`main --record`) or by generating spins, and test each wheel for uniformity
+over its 8 faces with a chi-squared test. For each replay, also print how
+lucky each player was.
+
+options:
+    --generate N    also count N generated spins of all five wheels
+    --seed S        RNG seed for --generate [default: 0]
+    -h, --help      print this message";
+
+fn fail(msg: &str) -> ! {
+    eprintln!("error: {}\n\n{}", msg, USAGE);
+    process::exit(1);
+}
+
+fn main() {
+    let mut paths: Vec<String> = Vec::new();
+    let mut generate: usize = 0;
+    let mut seed: u64 = 0;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || -> String {
+            args.next()
+                .unwrap_or_else(|| fail(&format!("missing value for {}", arg)))
+        };
+        match arg.as_str() {
+            "--generate" => {
+                generate = value().parse()
+                    .unwrap_or_else(|_| fail("invalid number of spins"));
+            },
+            "--seed" => {
+                seed = value().parse()
+                    .unwrap_or_else(|_| fail("invalid seed"));
+            },
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            },
+            _ if !arg.starts_with('-') => { paths.push(arg); },
+            _ => { fail(&format!("unknown argument '{}'", arg)); },
+        }
+    }
+    if paths.is_empty() && generate == 0 {
+        fail("expected replay files or --generate");
+    }
+
+    let mut fairness = Fairness::new();
+    for path in paths.iter() {
+        let replay = Replay::load(path).unwrap_or_else(|e| fail(&e));
+        fairness.add_replay(&replay)
+            .unwrap_or_else(|e| fail(&format!("{}: {}", path, e)));
+        println!("{}:", path);
+        for pos in [PlayerPos::P1, PlayerPos::P2] {
+            println!("  {:?} ({}): luck {}",
+                pos, replay.get_player(pos).name, Luck::of(&replay, pos));
+        }
+    }
+    if !paths.is_empty() { println!(); }
+    if generate > 0 {
+        let mut rng = StdRng::seed_from_u64(seed);
+        fairness.generate(generate, &mut rng);
+    }
+    print!("{}", fairness);
+}
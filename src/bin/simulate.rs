@@ -0,0 +1,108 @@
+use std::process;
+use wheels::engine::{
+    batch::Batch,
+    hero::HeroKind,
+    rules::{ Rules, TurnMode },
+    strategy::Strategy,
+    tournament::Entrant,
+};
+
+const USAGE: &str = "\
+usage: simulate [options]
+
+Play many games between two CPU players across all cores and print the
+totals. Results depend only on the seed, not on the number of threads.
+
+options:
+    --p1 L/R            Player 1's heroes [default: Warrior/Mage]
+    --p2 L/R            Player 2's heroes [default: Warrior/Mage]
+    --strategies A,B    strategies of Player 1 and 2 [default: classic,classic]
+                        (classic, advisor, random, idle)
+    --games N           number of games [default: 100000]
+    --seed S            master RNG seed [default: 0]
+    --threads N         play on N threads [default: one per core]
+    --quiet             don't report progress on stderr
+    --alternating       only the active player's rolls resolve each turn
+    --permanent-locks   locked wheels stay locked for the rest of the turn
+    --carry-over        excess EXP and energy carry over to the next level or
+                        rod
+    --max-turns N       end games after N turns, won by the higher Crown, then
+                        the higher Bulwark
+    --sudden-death N    bombs deal 1 more damage for every turn after turn N
+    -h, --help          print this message";
+
+fn fail(msg: &str) -> ! {
+    eprintln!("error: {}\n\n{}", msg, USAGE);
+    process::exit(1);
+}
+
+fn main() {
+    let mut heroes_p1 = (HeroKind::Warrior, HeroKind::Mage);
+    let mut heroes_p2 = (HeroKind::Warrior, HeroKind::Mage);
+    let mut strategies = (Strategy::Classic, Strategy::Classic);
+    let mut games: usize = 100_000;
+    let mut seed: u64 = 0;
+    let mut threads: Option<usize> = None;
+    let mut quiet = false;
+    let mut rules = Rules::default();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || -> String {
+            args.next()
+                .unwrap_or_else(|| fail(&format!("missing value for {}", arg)))
+        };
+        match arg.as_str() {
+            "--p1" => {
+                heroes_p1 = HeroKind::pair_from_name(&value())
+                    .unwrap_or_else(|e| fail(&e));
+            },
+            "--p2" => {
+                heroes_p2 = HeroKind::pair_from_name(&value())
+                    .unwrap_or_else(|e| fail(&e));
+            },
+            "--strategies" => {
+                strategies = Strategy::pair_from_name(&value())
+                    .unwrap_or_else(|e| fail(&e));
+            },
+            "--games" => {
+                games = value().parse()
+                    .unwrap_or_else(|_| fail("invalid number of games"));
+            },
+            "--seed" => {
+                seed = value().parse()
+                    .unwrap_or_else(|_| fail("invalid seed"));
+            },
+            "--threads" => {
+                threads = Some(value().parse()
+                    .unwrap_or_else(|_| fail("invalid number of threads")));
+            },
+            "--quiet" => { quiet = true; },
+            "--alternating" => { rules.turn_mode = TurnMode::Alternating; },
+            "--permanent-locks" => { rules.permanent_locks = true; },
+            "--carry-over" => { rules.carry_over = true; },
+            "--max-turns" => {
+                rules.max_turns = Some(value().parse()
+                    .unwrap_or_else(|_| fail("invalid number of turns")));
+            },
+            "--sudden-death" => {
+                rules.sudden_death = Some(value().parse()
+                    .unwrap_or_else(|_| fail("invalid number of turns")));
+            },
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            },
+            _ => { fail(&format!("unknown argument '{}'", arg)); },
+        }
+    }
+
+    let p1 = Entrant::new(strategies.0, heroes_p1.0, heroes_p1.1);
+    let p2 = Entrant::new(strategies.1, heroes_p2.0, heroes_p2.1);
+    let mut batch = Batch::new(p1, p2, games, seed, rules).progress(!quiet);
+    if let Some(threads) = threads { batch = batch.threads(threads); }
+    let results = batch.run().unwrap_or_else(|e| fail(&e));
+    println!("P1: {}", p1);
+    println!("P2: {}", p2);
+    print!("{}", results);
+}
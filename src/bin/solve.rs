@@ -0,0 +1,154 @@
+use std::process;
+use rand::{ SeedableRng, rngs::StdRng };
+use wheels::engine::{
+    builder::GameBuilder,
+    game::{ PlayerPos, Winner },
+    hero::HeroKind,
+    player::{ INIT_BULWARK, INIT_CROWN },
+    rules::{ Rules, TurnMode },
+    solver::{ Solver, MAX_STATES },
+    strategy::Strategy,
+};
+
+const USAGE: &str = "\
+usage: solve [options]
+
+Compute the exact probability that each player wins a game, with both
+players choosing locks by a fixed strategy.
+
+The number of reachable states grows very quickly with the number of turns
+left to play, so this is only practical for endgames, e.g. low Crowns or a
+turn limit close at hand.
+
+options:
+    --p1 L/R            Player 1's heroes [default: Warrior/Mage]
+    --p2 L/R            Player 2's heroes [default: Warrior/Mage]
+    --strategies A,B    strategies of Player 1 and 2 [default: classic,classic]
+                        (classic, advisor, random, idle)
+    --crowns A,B        starting Crown of Player 1 and 2 [default: 10,10]
+    --bulwarks A,B      starting Bulwark of Player 1 and 2 [default: 0,0]
+    --turn N            number of turns already played [default: 0]
+    --max-states N      give up after exploring N states [default: 10000]
+    --check N           also play N games by simulation and compare
+    --seed S            RNG seed for --check [default: 0]
+    --alternating       only the active player's rolls resolve each turn
+    --carry-over        excess EXP and energy carry over to the next level or
+                        rod
+    --max-turns N       end games after N turns, won by the higher Crown, then
+                        the higher Bulwark
+    --sudden-death N    bombs deal 1 more damage for every turn after turn N
+    -h, --help          print this message";
+
+fn fail(msg: &str) -> ! {
+    eprintln!("error: {}\n\n{}", msg, USAGE);
+    process::exit(1);
+}
+
+fn parse_pair(list: &str) -> (u8, u8) {
+    list.split_once(',')
+        .and_then(|(a, b)| a.parse().ok().zip(b.parse().ok()))
+        .unwrap_or_else(|| fail(&format!("expected two numbers in '{}'", list)))
+}
+
+fn main() {
+    let mut heroes_p1 = (HeroKind::Warrior, HeroKind::Mage);
+    let mut heroes_p2 = (HeroKind::Warrior, HeroKind::Mage);
+    let mut strategies = (Strategy::Classic, Strategy::Classic);
+    let mut crowns = (INIT_CROWN, INIT_CROWN);
+    let mut bulwarks = (INIT_BULWARK, INIT_BULWARK);
+    let mut turn: usize = 0;
+    let mut max_states: usize = MAX_STATES;
+    let mut check: usize = 0;
+    let mut seed: u64 = 0;
+    let mut rules = Rules::default();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || -> String {
+            args.next()
+                .unwrap_or_else(|| fail(&format!("missing value for {}", arg)))
+        };
+        match arg.as_str() {
+            "--p1" => {
+                heroes_p1 = HeroKind::pair_from_name(&value())
+                    .unwrap_or_else(|e| fail(&e));
+            },
+            "--p2" => {
+                heroes_p2 = HeroKind::pair_from_name(&value())
+                    .unwrap_or_else(|e| fail(&e));
+            },
+            "--strategies" => {
+                strategies = Strategy::pair_from_name(&value())
+                    .unwrap_or_else(|e| fail(&e));
+            },
+            "--crowns" => { crowns = parse_pair(&value()); },
+            "--bulwarks" => { bulwarks = parse_pair(&value()); },
+            "--turn" => {
+                turn = value().parse()
+                    .unwrap_or_else(|_| fail("invalid turn"));
+            },
+            "--max-states" => {
+                max_states = value().parse()
+                    .unwrap_or_else(|_| fail("invalid number of states"));
+            },
+            "--check" => {
+                check = value().parse()
+                    .unwrap_or_else(|_| fail("invalid number of games"));
+            },
+            "--seed" => {
+                seed = value().parse()
+                    .unwrap_or_else(|_| fail("invalid seed"));
+            },
+            "--alternating" => { rules.turn_mode = TurnMode::Alternating; },
+            "--carry-over" => { rules.carry_over = true; },
+            "--max-turns" => {
+                rules.max_turns = Some(value().parse()
+                    .unwrap_or_else(|_| fail("invalid number of turns")));
+            },
+            "--sudden-death" => {
+                rules.sudden_death = Some(value().parse()
+                    .unwrap_or_else(|_| fail("invalid number of turns")));
+            },
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            },
+            _ => { fail(&format!("unknown argument '{}'", arg)); },
+        }
+    }
+
+    let game
+        = GameBuilder::new(heroes_p1, heroes_p2)
+        .rules(rules)
+        .turn(turn)
+        .crown(PlayerPos::P1, crowns.0)
+        .crown(PlayerPos::P2, crowns.1)
+        .bulwark(PlayerPos::P1, bulwarks.0)
+        .bulwark(PlayerPos::P2, bulwarks.1)
+        .build()
+        .unwrap_or_else(|e| fail(&e));
+    let mut solver
+        = Solver::new(strategies.0, strategies.1)
+        .max_states(max_states);
+    let probs = solver.solve(&game).unwrap_or_else(|e| fail(&e));
+    println!("{:<10} {:>8} {:>8} {:>8}", "", "P1 wins", "P2 wins", "draw");
+    println!("{:<10} {:>8.4} {:>8.4} {:>8.4}",
+        "exact", probs.p1, probs.p2, probs.draw);
+    eprintln!("({} states)", solver.num_states());
+
+    if check > 0 {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut counts = [0_usize; 3];
+        for _ in 0..check {
+            let mut g = game.clone();
+            match g.run_headless(strategies.0, strategies.1, &mut rng).0 {
+                Winner::P1(_) => { counts[0] += 1; },
+                Winner::P2(_) => { counts[1] += 1; },
+                Winner::Draw(_) => { counts[2] += 1; },
+            }
+        }
+        let frac = |n: usize| n as f64 / check as f64;
+        println!("{:<10} {:>8.4} {:>8.4} {:>8.4}",
+            "simulated", frac(counts[0]), frac(counts[1]), frac(counts[2]));
+    }
+}
@@ -0,0 +1,141 @@
+use std::process;
+use wheels::engine::{
+    hero::HeroKind,
+    rules::{ Rules, TurnMode },
+    strategy::Strategy,
+    tournament::{ Entrant, Tournament, all_loadouts },
+};
+
+const USAGE: &str = "\
+usage: tournament [options]
+
+Play every pair of CPU strategies and hero loadouts against each other and
+print a table of Elo ratings.
+
+options:
+    --games N           games per pair in each seating [default: 1]
+    --seed S            master RNG seed [default: 0]
+    --strategies LIST   comma-separated strategies, or `all` [default: all]
+                        (classic, advisor, random, idle)
+    --loadouts LIST     comma-separated `Left/Right` hero pairs, or `all`
+                        [default: all]
+    --alternating       only the active player's rolls resolve each turn
+    --permanent-locks   locked wheels stay locked for the rest of the turn
+    --carry-over        excess EXP and energy carry over to the next level or
+                        rod
+    --max-turns N       end games after N turns, won by the higher Crown, then
+                        the higher Bulwark
+    --sudden-death N    bombs deal 1 more damage for every turn after turn N
+    --threads N         play on N threads [default: one per core]
+    --quiet             don't report progress on stderr
+    -h, --help          print this message";
+
+fn fail(msg: &str) -> ! {
+    eprintln!("error: {}\n\n{}", msg, USAGE);
+    process::exit(1);
+}
+
+fn parse_strategies(list: &str) -> Vec<Strategy> {
+    if list == "all" { return Strategy::all().to_vec(); }
+    list.split(',')
+        .map(|name| {
+            Strategy::from_name(name)
+                .unwrap_or_else(|| {
+                    fail(&format!("unknown strategy '{}'", name))
+                })
+        })
+        .collect()
+}
+
+fn parse_loadouts(list: &str) -> Vec<(HeroKind, HeroKind)> {
+    if list == "all" { return all_loadouts(); }
+    list.split(',')
+        .map(|pair| {
+            HeroKind::pair_from_name(pair).unwrap_or_else(|e| fail(&e))
+        })
+        .collect()
+}
+
+fn main() {
+    let mut games: usize = 1;
+    let mut seed: u64 = 0;
+    let mut strategies: Vec<Strategy> = Strategy::all().to_vec();
+    let mut loadouts: Vec<(HeroKind, HeroKind)> = all_loadouts();
+    let mut rules = Rules::default();
+    let mut threads: Option<usize> = None;
+    let mut quiet = false;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || -> String {
+            args.next()
+                .unwrap_or_else(|| fail(&format!("missing value for {}", arg)))
+        };
+        match arg.as_str() {
+            "--games" => {
+                games = value().parse()
+                    .unwrap_or_else(|_| fail("invalid number of games"));
+            },
+            "--seed" => {
+                seed = value().parse()
+                    .unwrap_or_else(|_| fail("invalid seed"));
+            },
+            "--strategies" => { strategies = parse_strategies(&value()); },
+            "--loadouts" => { loadouts = parse_loadouts(&value()); },
+            "--alternating" => { rules.turn_mode = TurnMode::Alternating; },
+            "--permanent-locks" => { rules.permanent_locks = true; },
+            "--carry-over" => { rules.carry_over = true; },
+            "--max-turns" => {
+                rules.max_turns = Some(value().parse()
+                    .unwrap_or_else(|_| fail("invalid number of turns")));
+            },
+            "--sudden-death" => {
+                rules.sudden_death = Some(value().parse()
+                    .unwrap_or_else(|_| fail("invalid number of turns")));
+            },
+            "--threads" => {
+                threads = Some(value().parse()
+                    .unwrap_or_else(|_| fail("invalid number of threads")));
+            },
+            "--quiet" => { quiet = true; },
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            },
+            _ => { fail(&format!("unknown argument '{}'", arg)); },
+        }
+    }
+
+    let entrants: Vec<Entrant>
+        = strategies.iter()
+        .flat_map(|strat| {
+            loadouts.iter().map(|(l, r)| Entrant::new(*strat, *l, *r))
+        })
+        .collect();
+    let mut tournament
+        = Tournament::new(entrants, games, seed, rules)
+        .progress(!quiet);
+    if let Some(threads) = threads { tournament = tournament.threads(threads); }
+    if !quiet { eprintln!("playing {} games...", tournament.num_games()); }
+    let standings = tournament.run().unwrap_or_else(|e| fail(&e));
+
+    println!(
+        "{:>4}  {:>7}  {:>5}  {:>5}  {:>5}  {:>5}  {:<8}  {:<8}  {:<8}",
+        "rank", "rating", "won", "lost", "drawn", "turns",
+        "strategy", "left", "right",
+    );
+    for (rank, standing) in standings.iter().enumerate() {
+        println!(
+            "{:>4}  {:>7.1}  {:>5}  {:>5}  {:>5}  {:>5.1}  {:<8}  {:<8}  {:<8}",
+            rank + 1,
+            standing.rating,
+            standing.wins,
+            standing.losses,
+            standing.draws,
+            standing.mean_turns(),
+            standing.entrant.strategy.to_string(),
+            standing.entrant.hero_l.to_string(),
+            standing.entrant.hero_r.to_string(),
+        );
+    }
+}
@@ -1,11 +1,269 @@
-use wheels::engine::game::{ Game, Winner };
+use std::{
+    io::{ self, Write },
+    path::Path,
+    process,
+};
+use wheels::engine::{
+    game::{ Game, PlayerPos, Winner },
+    handicap::Handicap,
+    meter::WinMeter,
+    player::{ HeroPos, Player },
+    profile::{ Outcome, Profiles },
+    rules::{ Rules, TurnMode },
+    series::Series,
+};
+
+const USAGE: &str = "\
+usage: main [options]
+
+Play a game of Wheels against the computer. Results are saved to your
+profile in $WHEELS_DATA_DIR, $XDG_DATA_HOME/wheels or ~/.local/share/wheels.
+
+options:
+    --record FILE   save a replay of the match to FILE (see `blunders`)
+    --draft         choose heroes by a draft of alternating bans, then picks
+                    in snake order
+    --alternating   take turns with the computer; only the rolls of the
+                    player whose turn it is resolve
+    --permanent-locks
+                    locked wheels stay locked for the rest of the turn
+    --carry-over    excess EXP and energy carry over to the next level or rod
+    --max-turns N   end the game after N turns, won by the higher Crown, then
+                    the higher Bulwark
+    --sudden-death N
+                    bombs deal 1 more damage for every turn after turn N
+    --handicap      choose a handicap for each player after choosing heroes
+    --handicap1 SPEC, --handicap2 SPEC
+                    give Player 1 (you) or 2 (the computer) a handicap, as
+                    comma-separated `key=value` pairs with keys crown,
+                    bulwark, level, exp (of both heroes) and spins (extra
+                    re-spins per turn), e.g. `crown=12,spins=1`
+    --series N      play a best-of-N series (N odd, e.g. 3, 5 or 7), swapping
+                    who is Player 1 after every game; with --record, game k
+                    is saved to FILE with `-k` added to its name
+    --repick        choose heroes again before every game of a series
+    --win-meter     show each player's estimated chance to win on the board,
+                    and how much the last turn changed it
+    --profiles      print every saved profile and exit
+    -h, --help      print this message";
+
+fn fail(msg: &str) -> ! {
+    eprintln!("error: {}\n\n{}", msg, USAGE);
+    process::exit(1);
+}
+
+fn print_profiles() {
+    let profiles = Profiles::load_default().unwrap_or_else(|e| fail(&e));
+    if profiles.get_profiles().is_empty() {
+        println!("No profiles saved in {}", profiles.get_path().display());
+    }
+    for profile in profiles.get_profiles().iter() {
+        print!("{}", profile);
+    }
+}
 
 fn main() {
+    let mut record: Option<String> = None;
+    let mut rules = Rules::default();
+    let mut draft = false;
+    let mut handicap_menu = false;
+    let mut handicaps: [Option<Handicap>; 2] = [None, None];
+    let mut best_of: Option<usize> = None;
+    let mut repick = false;
+    let mut win_meter = false;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || -> String {
+            args.next()
+                .unwrap_or_else(|| fail(&format!("missing value for {}", arg)))
+        };
+        match arg.as_str() {
+            "--record" => { record = Some(value()); },
+            "--draft" => { draft = true; },
+            "--alternating" => { rules.turn_mode = TurnMode::Alternating; },
+            "--permanent-locks" => { rules.permanent_locks = true; },
+            "--carry-over" => { rules.carry_over = true; },
+            "--max-turns" => {
+                rules.max_turns = Some(value().parse()
+                    .unwrap_or_else(|_| fail("invalid number of turns")));
+            },
+            "--sudden-death" => {
+                rules.sudden_death = Some(value().parse()
+                    .unwrap_or_else(|_| fail("invalid number of turns")));
+            },
+            "--handicap" => { handicap_menu = true; },
+            "--handicap1" | "--handicap2" => {
+                let k = if arg == "--handicap1" { 0 } else { 1 };
+                handicaps[k] = Some(value().parse()
+                    .unwrap_or_else(|e: String| fail(&e)));
+            },
+            "--series" => {
+                best_of = Some(value().parse()
+                    .unwrap_or_else(|_| fail("invalid series length")));
+            },
+            "--repick" => { repick = true; },
+            "--win-meter" => { win_meter = true; },
+            "--profiles" => {
+                print_profiles();
+                return;
+            },
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            },
+            _ => { fail(&format!("unknown argument '{}'", arg)); },
+        }
+    }
+
     println!("Welcome to Wheels!");
-    let winner = Game::get_choose_singleplayer().run_singleplayer();
-    match winner {
-        Winner::P1 => { println!("Player 1 wins!"); },
-        Winner::P2 => { println!("Player 2 wins!"); },
-        Winner::Draw => { println!("Draw!"); },
+    println!("(Enter wheel numbers to lock or unlock them before re-spinning, \
+        or `?` to ask the advisor.)");
+    let mut game
+        = if draft {
+            Game::get_choose_draft(true)
+        } else {
+            Game::get_choose_singleplayer()
+        };
+    game.set_rules(rules);
+    for (pos, handicap) in [PlayerPos::P1, PlayerPos::P2].into_iter()
+        .zip(handicaps)
+    {
+        match handicap {
+            Some(handicap) => {
+                game.set_handicap(pos, handicap)
+                    .unwrap_or_else(|e| fail(&e));
+            },
+            None if handicap_menu => { game.get_choose_handicap(pos); },
+            None => { },
+        }
+    }
+
+    let Some(best_of) = best_of else {
+        play(&mut game, PlayerPos::P1, record.as_deref(), win_meter);
+        return;
+    };
+    let mut series
+        = Series::new(
+            best_of,
+            game.get_player(PlayerPos::P1).get_name(),
+            game.get_player(PlayerPos::P2).get_name(),
+        )
+        .unwrap_or_else(|e| fail(&e));
+    loop {
+        let n = series.get_games().len() + 1;
+        println!("\n=== Game {} ===", n);
+        let human = series.next_seat(0);
+        if human == PlayerPos::P2 { game.swap_players(); }
+        let path = record.as_deref().map(|path| series_path(path, n));
+        let winner = play(&mut game, human, path.as_deref(), win_meter);
+        let p1 = if human == PlayerPos::P1 { 0 } else { 1 };
+        series.record(p1, winner, game.get_turn());
+        println!("\n{}", series.scoreboard());
+        if series.is_over() { break; }
+
+        wait_enter("Press ENTER to start the next game: ");
+        let human_player = game.get_player(human).restart();
+        let cpu_player = game.get_player(human.other()).restart();
+        let handicaps
+            = [*human_player.get_handicap(), *cpu_player.get_handicap()];
+        game
+            = if !repick {
+                Game::new(human_player, cpu_player)
+            } else if draft {
+                Game::get_choose_draft_named(
+                    human_player.get_name(), cpu_player.get_name(), true)
+            } else {
+                Game::new(
+                    Player::get_choose_heroes(human_player.get_name()),
+                    Player::get_choose_cpu(),
+                )
+            };
+        game.set_rules(rules);
+        // handicaps stay with each side even when heroes are re-picked
+        for (pos, handicap) in [PlayerPos::P1, PlayerPos::P2].into_iter()
+            .zip(handicaps)
+        {
+            game.set_handicap(pos, handicap).unwrap_or_else(|e| fail(&e));
+        }
+    }
+    println!("\n{}", series.summary());
+}
+
+/// Add a finished game to the saved profile of the player at `human`.
+fn record_profile(game: &Game, human: PlayerPos, winner: Winner) {
+    let player = game.get_player(human);
+    if player.get_name().is_empty() { return; }
+    let mut profiles
+        = match Profiles::load_default() {
+            Ok(profiles) => profiles,
+            Err(e) => {
+                eprintln!("error loading profiles: {}", e);
+                return;
+            },
+        };
+    let heroes = (
+        player.get_hero(HeroPos::L).get_kind(),
+        player.get_hero(HeroPos::R).get_kind(),
+    );
+    profiles.record(player.get_name(), heroes, Outcome::of(winner, human));
+    if let Err(e) = profiles.save() {
+        eprintln!("error saving profiles: {}", e);
+    }
+}
+
+/// Play a game against the computer with the user seated as `human`, and
+/// announce the winner.
+fn play(
+    game: &mut Game,
+    human: PlayerPos,
+    record: Option<&str>,
+    win_meter: bool,
+) -> Winner
+{
+    if win_meter { game.set_win_meter(Some(WinMeter::new())); }
+    if record.is_some() {
+        game.start_replay(human == PlayerPos::P2, human == PlayerPos::P1);
     }
+    let winner = game.run_vs_cpu(human);
+    record_profile(game, human, winner);
+    match winner.player() {
+        Some(pos) => {
+            let n = if pos == PlayerPos::P1 { 1 } else { 2 };
+            println!("Player {} ({}) wins! ({})",
+                n, game.get_player(pos).get_name(), winner.reason());
+        },
+        None => { println!("Draw! ({})", winner.reason()); },
+    }
+    if let Some(stats) = game.get_stats() {
+        println!();
+        print!("{}", stats);
+    }
+    if let (Some(path), Some(replay)) = (record, game.get_replay()) {
+        match replay.save(path) {
+            Ok(()) => { println!("Replay saved to {}", path); },
+            Err(e) => { eprintln!("{}", e); },
+        }
+    }
+    winner
+}
+
+/// Path of the replay of game number `n` of a series, e.g. `match-2.txt` for
+/// `match.txt`.
+fn series_path(path: &str, n: usize) -> String {
+    let path = Path::new(path);
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name
+        = match path.extension() {
+            Some(ext) => format!("{}-{}.{}", stem, n, ext.to_string_lossy()),
+            None => format!("{}-{}", stem, n),
+        };
+    path.with_file_name(name).to_string_lossy().into_owned()
+}
+
+fn wait_enter(prompt: &str) {
+    let mut _buf = String::new();
+    print!("{}", prompt);
+    io::stdout().flush().unwrap();
+    io::stdin().read_line(&mut _buf).expect("error reading ENTER");
 }
@@ -0,0 +1,42 @@
+//! Parallel simulation must give the same results on any number of threads.
+
+use wheels::engine::{
+    batch::Batch,
+    hero::HeroKind,
+    rules::Rules,
+    strategy::Strategy,
+    tournament::{ Entrant, Tournament },
+};
+
+fn entrants() -> (Entrant, Entrant) {
+    (
+        Entrant::new(Strategy::Classic, HeroKind::Warrior, HeroKind::Mage),
+        Entrant::new(Strategy::Random, HeroKind::Priest, HeroKind::Archer),
+    )
+}
+
+#[test]
+fn batch_is_independent_of_threads() {
+    let (p1, p2) = entrants();
+    let batch = Batch::new(p1, p2, 2_000, 7, Rules::default());
+    let one = batch.clone().threads(1).run().unwrap();
+    let four = batch.clone().threads(4).run().unwrap();
+    let global = batch.run().unwrap();
+    assert_eq!(one.games(), 2_000);
+    assert_eq!(one, four);
+    assert_eq!(one, global);
+}
+
+#[test]
+fn tournament_is_independent_of_threads() {
+    let (p1, p2) = entrants();
+    let tournament = Tournament::new(vec![p1, p2], 50, 7, Rules::default());
+    let describe = |threads: usize| -> Vec<String> {
+        tournament.clone().threads(threads).run().unwrap()
+            .iter()
+            .map(|s| format!("{} {:.6} {} {} {} {}",
+                s.entrant, s.rating, s.wins, s.losses, s.draws, s.turns))
+            .collect()
+    };
+    assert_eq!(describe(1), describe(4));
+}
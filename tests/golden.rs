@@ -0,0 +1,216 @@
+//! Golden-file regression tests for turn resolution.
+//!
+//! Each file in `tests/golden/` describes a single turn:
+//! ```text
+//! # comment
+//! state P1 crown=10 bulwark=0
+//! hero P1 L Warrior level=0 exp=0 energy=2 act=false
+//! hero P1 R Mage level=0 exp=0 energy=0 act=false
+//! state P2 crown=10 bulwark=0
+//! hero P2 L Priest level=0 exp=0 energy=0 act=false
+//! hero P2 R Archer level=0 exp=0 energy=0 act=false
+//! rolls P1 S S S H H
+//! rolls P2 H H H H H
+//! turn 1
+//! rules carry-over max-turns=20 sudden-death=10
+//! expect
+//! log Player 1 (Alice)'s Warrior gains 1 energy
+//! ...
+//! winner P1
+//! state P1 crown=10 bulwark=0
+//! ...
+//! ```
+//! The `turn` line (turn number, default 1) and `rules` line (rule variants in
+//! play) are optional.
+//! Everything above `expect` is fed through `Game::do_turn_partial_rng`, with
+//! only the rolls of players whose turn it is, and everything below is
+//! compared against the resulting log, winner (if any) and state of both
+//! players. Set `WHEELS_BLESS=1` to overwrite the expected
+//! results with the actual ones.
+
+use std::{ fs, path::{ Path, PathBuf } };
+use rand::{ SeedableRng, rngs::StdRng };
+use wheels::engine::{
+    game::{ Game, PlayerPos },
+    replay::{ StateReader, fmt_player_state },
+    rules::Rules,
+    wheel::{ Rolls, parse_rolls },
+};
+
+const NAME_P1: &str = "Alice";
+const NAME_P2: &str = "Bob";
+const EXPECT: &str = "expect";
+
+struct Case {
+    /// Lines up to and including `expect`, verbatim.
+    header: Vec<String>,
+    game: Game,
+    rolls_p1: Rolls,
+    rolls_p2: Rolls,
+    expected: Vec<String>,
+}
+
+fn is_content(line: &str) -> bool {
+    let line = line.trim();
+    !line.is_empty() && !line.starts_with('#')
+}
+
+fn read_case(text: &str) -> Result<Case, String> {
+    let mut header: Vec<String> = Vec::new();
+    let mut states = StateReader::new();
+    let mut rolls_p1: Option<Rolls> = None;
+    let mut rolls_p2: Option<Rolls> = None;
+    let mut rules = Rules::default();
+    let mut turn: usize = 1;
+    let mut lines = text.lines().enumerate();
+    for (n, line) in lines.by_ref() {
+        let err = |e: String| format!("line {}: {}", n + 1, e);
+        header.push(line.to_string());
+        if !is_content(line) { continue; }
+        if line.trim() == EXPECT { break; }
+        if states.read_line(line).map_err(err)? { continue; }
+        match line.split_once(' ') {
+            Some(("rolls", rest)) => {
+                let (pos, rolls) = rest.split_once(' ').unwrap_or((rest, ""));
+                let rolls = parse_rolls(rolls).map_err(err)?;
+                match pos {
+                    "P1" => { rolls_p1 = Some(rolls); },
+                    "P2" => { rolls_p2 = Some(rolls); },
+                    _ => {
+                        return Err(err(format!("invalid player '{}'", pos)));
+                    },
+                }
+            },
+            Some(("turn", n)) => {
+                turn = n.trim().parse()
+                    .ok().filter(|n| *n > 0)
+                    .ok_or_else(|| err(format!("invalid turn '{}'", n)))?;
+            },
+            Some(("rules", rest)) => {
+                rules = rest.parse().map_err(err)?;
+            },
+            _ => { return Err(err(format!("unexpected line '{}'", line))); },
+        }
+    }
+    if header.last().map(|l| l.trim()) != Some(EXPECT) {
+        return Err(format!("missing '{}' line", EXPECT));
+    }
+    let (p1, p2) = states.finish(NAME_P1, NAME_P2)?;
+    let mut game = Game::new(p1, p2);
+    game.set_rules(rules);
+    game.set_turn(turn - 1);
+    let expected: Vec<String>
+        = lines
+        .filter(|(_, line)| is_content(line))
+        .map(|(_, line)| line.trim().to_string())
+        .collect();
+    Ok(Case {
+        header,
+        game,
+        rolls_p1: rolls_p1.ok_or("missing rolls for P1")?,
+        rolls_p2: rolls_p2.ok_or("missing rolls for P2")?,
+        expected,
+    })
+}
+
+fn run_case(case: &mut Case) -> Vec<String> {
+    let mut rng = StdRng::seed_from_u64(0);
+    let turn = case.game.get_turn() + 1;
+    let mode = case.game.get_rules().turn_mode;
+    let rolls_p1
+        = mode.is_active(PlayerPos::P1, turn).then_some(&case.rolls_p1);
+    let rolls_p2
+        = mode.is_active(PlayerPos::P2, turn).then_some(&case.rolls_p2);
+    let (winner, log)
+        = case.game.do_turn_partial_rng(rolls_p1, rolls_p2, &mut rng);
+    let mut actual: Vec<String>
+        = log.iter()
+        .map(|action| format!("log {}", action.msg(&case.game)))
+        .collect();
+    if let Some(winner) = winner {
+        actual.push(format!("winner {:?}", winner));
+    }
+    for pos in [PlayerPos::P1, PlayerPos::P2] {
+        let state = fmt_player_state(pos, case.game.get_player(pos));
+        actual.extend(state.lines().map(String::from));
+    }
+    actual
+}
+
+/// Render a line-by-line diff of `expected` against `actual`.
+fn diff(expected: &[String], actual: &[String]) -> String {
+    // longest common subsequence, by dynamic programming
+    let (n, m) = (expected.len(), actual.len());
+    let mut lcs = vec![vec![0_usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j]
+                = if expected[i] == actual[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+        }
+    }
+    let mut out: Vec<String> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && expected[i] == actual[j] {
+            out.push(format!("  {}", expected[i]));
+            i += 1;
+            j += 1;
+        } else if i < n && (j == m || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            out.push(format!("- {}", expected[i]));
+            i += 1;
+        } else {
+            out.push(format!("+ {}", actual[j]));
+            j += 1;
+        }
+    }
+    out.join("\n")
+}
+
+fn case_files() -> Vec<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    let mut files: Vec<PathBuf>
+        = fs::read_dir(&dir)
+        .unwrap_or_else(|e| panic!("error reading {}: {}", dir.display(), e))
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .collect();
+    files.sort();
+    files
+}
+
+#[test]
+fn golden() {
+    let bless = std::env::var_os("WHEELS_BLESS").is_some();
+    let files = case_files();
+    assert!(!files.is_empty(), "no golden files found");
+    let mut failures: Vec<String> = Vec::new();
+    for path in files.iter() {
+        let name = path.file_name().unwrap().to_string_lossy();
+        let text = fs::read_to_string(path).unwrap();
+        let mut case
+            = match read_case(&text) {
+                Ok(case) => case,
+                Err(e) => {
+                    failures.push(format!("{}: {}", name, e));
+                    continue;
+                },
+            };
+        let actual = run_case(&mut case);
+        if actual == case.expected { continue; }
+        if bless {
+            let mut lines = case.header.clone();
+            lines.extend(actual);
+            fs::write(path, lines.join("\n") + "\n").unwrap();
+        } else {
+            failures.push(
+                format!("{}:\n{}", name, diff(&case.expected, &actual)));
+        }
+    }
+    assert!(failures.is_empty(),
+        "{} of {} golden cases failed (set WHEELS_BLESS=1 to update):\n\n{}",
+        failures.len(), files.len(), failures.join("\n\n"));
+}
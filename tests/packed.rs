@@ -0,0 +1,172 @@
+//! Property tests for `PackedState`: packing any valid game and unpacking it
+//! again must give back the same state.
+
+use proptest::prelude::*;
+use rand::{ SeedableRng, rngs::StdRng };
+use wheels::engine::{
+    game::{ Game, PlayerPos },
+    handicap::Handicap,
+    hero::{ Hero, HeroKind, MAX_EXP, MAX_LEVEL },
+    packed::{ PackedMap, PackedState },
+    player::{ Player, HeroPos, MAX_BULWARK, MAX_CROWN },
+    replay::fmt_player_state,
+    rules::{ Rules, TurnMode },
+    strategy,
+};
+
+/// Everything that a `PackedState` should preserve.
+fn describe(game: &Game) -> String {
+    let mut out
+        = format!("turn {} rules {:?}\n", game.get_turn(), game.get_rules());
+    for pos in [PlayerPos::P1, PlayerPos::P2] {
+        let player = game.get_player(pos);
+        out += &fmt_player_state(pos, player);
+        out += &format!("spins {}\n", player.get_handicap().spins);
+    }
+    out
+}
+
+fn hero() -> impl Strategy<Value = Hero> {
+    (0..6_usize, 0..=MAX_LEVEL, 0..MAX_EXP, 0..=5_u8, any::<bool>())
+        .prop_map(|(kind, level, exp, energy, act)| {
+            let mut hero = Hero::from_kind(HeroKind::all()[kind]);
+            hero.set_level(level).unwrap();
+            hero.set_exp(exp).unwrap();
+            hero.set_energy(energy.min(hero.get_rod_len())).unwrap();
+            hero.set_act(act);
+            hero
+        })
+}
+
+fn player() -> impl Strategy<Value = Player> {
+    // packed state only has room for 15 extra re-spins
+    (hero(), hero(), 0..=MAX_CROWN, 0..=MAX_BULWARK, 0..=15_u8)
+        .prop_map(|(hero_l, hero_r, crown, bulwark, spins)| {
+            let handicap = Handicap { spins, ..Handicap::default() };
+            let mut player
+                = Player::new_handicap(
+                    "",
+                    Hero::from_kind(hero_l.get_kind()),
+                    Hero::from_kind(hero_r.get_kind()),
+                    handicap,
+                )
+                .unwrap();
+            player.set_crown(crown).unwrap();
+            player.set_bulwark(bulwark).unwrap();
+            *player.get_hero_mut(HeroPos::L) = hero_l;
+            *player.get_hero_mut(HeroPos::R) = hero_r;
+            player
+        })
+}
+
+fn rules() -> impl Strategy<Value = Rules> {
+    (
+        any::<bool>(),
+        any::<bool>(),
+        any::<bool>(),
+        proptest::option::of(1..1000_usize),
+        proptest::option::of(0..1000_usize),
+    )
+        .prop_map(|(alternating, permanent_locks, carry_over, max, sudden)| {
+            Rules {
+                turn_mode: if alternating {
+                    TurnMode::Alternating
+                } else {
+                    TurnMode::BothRoll
+                },
+                permanent_locks,
+                carry_over,
+                max_turns: max,
+                sudden_death: sudden,
+            }
+        })
+}
+
+fn game() -> impl Strategy<Value = Game> {
+    (player(), player(), rules(), 0..1000_usize)
+        .prop_map(|(p1, p2, rules, turn)| {
+            let mut game = Game::new(p1, p2);
+            game.set_rules(rules);
+            game.set_turn(turn);
+            game
+        })
+        .prop_filter("invalid game", |game| game.validate().is_ok())
+}
+
+proptest! {
+    #[test]
+    fn round_trip(game in game()) {
+        let packed = PackedState::new(&game).unwrap();
+        let unpacked = packed.to_game();
+        prop_assert_eq!(describe(&unpacked), describe(&game));
+        prop_assert_eq!(PackedState::new(&unpacked).unwrap(), packed);
+        prop_assert_eq!(PackedState::from_bits(packed.to_bits()), Ok(packed));
+    }
+
+    #[test]
+    fn from_bits_is_canonical(bits in 0..1_u128 << 121) {
+        if let Ok(packed) = PackedState::from_bits(bits) {
+            prop_assert_eq!(packed.to_bits(), bits);
+            let repacked = PackedState::new(&packed.to_game()).unwrap();
+            prop_assert_eq!(repacked, packed);
+        }
+    }
+
+    #[test]
+    fn round_trip_in_play(
+        heroes in [(0..6_usize, 1..6_usize), (0..6_usize, 1..6_usize)],
+        rules in rules(),
+        seed in any::<u64>(),
+    ) {
+        // a new game between distinct heroes
+        let [p1, p2]
+            = heroes.map(|(l, d)| {
+                let kinds = HeroKind::all();
+                Player::new(
+                    "",
+                    Hero::from_kind(kinds[l]),
+                    Hero::from_kind(kinds[(l + d) % kinds.len()]),
+                )
+            });
+        let mut game = Game::new(p1, p2);
+        game.set_rules(rules);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut seen: PackedMap<usize> = PackedMap::default();
+        for turn in 0..40 {
+            let packed = PackedState::new(&game).unwrap();
+            prop_assert_eq!(describe(&packed.to_game()), describe(&game));
+            // the turn number is part of the state, so no two turns collide
+            prop_assert_eq!(seen.insert(packed, turn), None);
+            let p1
+                = game.get_rolls_strategy(
+                    PlayerPos::P1, strategy::Strategy::Classic, &mut rng);
+            let p2
+                = game.get_rolls_strategy(
+                    PlayerPos::P2, strategy::Strategy::Random, &mut rng);
+            if game.do_turn_rng(&p1, &p2, &mut rng).0.is_some() { break; }
+        }
+    }
+}
+
+#[test]
+fn too_many_spins() {
+    let handicap = Handicap { spins: 16, ..Handicap::default() };
+    let p1
+        = Player::new_handicap(
+            "",
+            Hero::from_kind(HeroKind::Warrior),
+            Hero::from_kind(HeroKind::Mage),
+            handicap,
+        )
+        .unwrap();
+    let p2
+        = Player::new(
+            "",
+            Hero::from_kind(HeroKind::Archer),
+            Hero::from_kind(HeroKind::Priest),
+        );
+    assert_eq!(
+        PackedState::new(&Game::new(p1, p2)),
+        Err("extra re-spins 16 does not fit in 4 bits".to_string()),
+    );
+}
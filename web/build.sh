@@ -0,0 +1,10 @@
+#!/bin/sh
+# Build the engine for the browser into web/pkg. Needs the wasm32 target
+# (`rustup target add wasm32-unknown-unknown`) and a wasm-bindgen CLI of the
+# same version as the wasm-bindgen crate
+# (`cargo install wasm-bindgen-cli --version <version>`).
+set -e
+cd "$(dirname "$0")/.."
+cargo build -p wheels-web --release --target wasm32-unknown-unknown
+wasm-bindgen --target web --no-typescript --out-dir web/pkg \
+    target/wasm32-unknown-unknown/release/wheels_web.wasm
@@ -0,0 +1,96 @@
+// Browser front end: renders a `WebGame` and forwards clicks to it.
+
+import init, { WebGame } from "./pkg/wheels_web.js";
+
+const HEROES = ["Warrior", "Mage", "Archer", "Engineer", "Assassin", "Priest"];
+const DEFAULTS = {
+  left: "Warrior", right: "Mage", cpuLeft: "Priest", cpuRight: "Archer",
+};
+
+let game = null;
+
+const $ = (id) => document.getElementById(id);
+
+function renderPlayer(player, maxExp, maxLevel) {
+  const heroes = player.heroes.map((hero, k) => `
+    <p>${k === 0 ? "Left" : "Right"}: ${hero.kind}<br>
+      &nbsp;&nbsp;Energy: ${hero.energy} / ${hero.rodLen}<br>
+      &nbsp;&nbsp;EXP: ${hero.exp} / ${maxExp}<br>
+      &nbsp;&nbsp;Level: ${hero.level} / ${maxLevel}</p>`);
+  return `
+    <h3>${player.name}</h3>
+    <p>Crown: ${player.crown}<br>Bulwark: ${player.bulwark}</p>
+    ${heroes.join("")}`;
+}
+
+function renderWheels(el, rolls, locks, onClick) {
+  el.replaceChildren(...rolls.map((panel, k) => {
+    const wheel = document.createElement("button");
+    wheel.className = locks[k] ? "wheel locked" : "wheel";
+    wheel.textContent = panel;
+    wheel.disabled = !onClick;
+    if (onClick) { wheel.onclick = () => onClick(k); }
+    return wheel;
+  }));
+}
+
+function render() {
+  const state = JSON.parse(game.state());
+  const over = state.winner !== null;
+  $("turn").textContent = `Turn ${state.turn + 1}`;
+  $("p1").innerHTML = renderPlayer(state.players[0], state.maxExp,
+    state.maxLevel);
+  $("p2").innerHTML = renderPlayer(state.players[1], state.maxExp,
+    state.maxLevel);
+  const canLock = !over && state.spinsLeft > 0;
+  renderWheels($("rolls"), state.rolls, state.locks, canLock ? (k) => {
+    game.toggleLock(k);
+    render();
+  } : null);
+  renderWheels($("cpu-rolls"), state.cpuRolls ?? [], [], null);
+  $("respin").textContent = `Re-spin (${state.spinsLeft} left)`;
+  $("respin").disabled = !canLock;
+  $("play").disabled = over;
+  if (over) {
+    const { player, reason } = state.winner;
+    $("winner").textContent = player === null
+      ? `Draw! (${reason})`
+      : `${state.players[player - 1].name} won! (${reason})`;
+  } else {
+    $("winner").textContent = "";
+  }
+}
+
+$("respin").onclick = () => {
+  game.respin();
+  render();
+};
+
+$("play").onclick = () => {
+  const log = game.doTurn();
+  $("log").textContent = log.map((msg) => `> ${msg}`).join("\n");
+  render();
+};
+
+$("setup").onsubmit = (event) => {
+  event.preventDefault();
+  const form = new FormData(event.target);
+  const seed = BigInt(Math.floor(Math.random() * Number.MAX_SAFE_INTEGER));
+  try {
+    game = new WebGame(form.get("left"), form.get("right"),
+      form.get("cpuLeft"), form.get("cpuRight"), seed);
+  } catch (e) {
+    alert(e.message);
+    return;
+  }
+  $("log").textContent = "";
+  $("game").hidden = false;
+  render();
+};
+
+for (const select of $("setup").querySelectorAll("select")) {
+  for (const hero of HEROES) { select.add(new Option(hero)); }
+  select.value = DEFAULTS[select.name];
+}
+
+await init();
@@ -0,0 +1,149 @@
+//! Bindings of the `wheels` engine for the browser, through `wasm-bindgen`.
+//!
+//! A [`WebGame`] is a game between you, as Player 1, and the computer, which
+//! locks by [`Strategy::Classic`]. Every random draw comes from an RNG seeded
+//! by the caller, so nothing here touches the terminal, threads or the OS.
+
+use rand::{ SeedableRng, rngs::StdRng };
+use serde_json::json;
+use wasm_bindgen::prelude::*;
+use wheels::engine::{
+    game::{ Game, PlayerPos, Winner },
+    hero::{ Hero, HeroKind, MAX_EXP, MAX_LEVEL },
+    json::{ player_json, rolls_json, winner_json },
+    player::Player,
+    strategy::Strategy,
+    wheel::{ Wheel, Rolls },
+};
+
+fn hero_kind(name: &str) -> Result<HeroKind, String> {
+    HeroKind::from_name(name).ok_or_else(|| format!("unknown hero '{}'", name))
+}
+
+fn new_player(name: &str, hero_l: &str, hero_r: &str)
+    -> Result<Player, String>
+{
+    let (hero_l, hero_r) = (hero_kind(hero_l)?, hero_kind(hero_r)?);
+    if hero_l == hero_r {
+        return Err(format!("{} can't have two {}s", name, hero_l));
+    }
+    Ok(Player::new(name, Hero::from_kind(hero_l), Hero::from_kind(hero_r)))
+}
+
+/// A game against the computer, spun and locked one wheel at a time from
+/// JavaScript.
+#[wasm_bindgen]
+pub struct WebGame {
+    game: Game,
+    rng: StdRng,
+    /// Your rolls for the current turn.
+    rolls: Rolls,
+    locks: [bool; 5],
+    spins_left: usize,
+    /// The computer's final rolls on the last turn.
+    cpu_rolls: Option<Rolls>,
+    winner: Option<Winner>,
+}
+
+#[wasm_bindgen]
+impl WebGame {
+    /// Start a game with your heroes `left` and `right` against the
+    /// computer's `cpu_left` and `cpu_right`, by name.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        left: &str,
+        right: &str,
+        cpu_left: &str,
+        cpu_right: &str,
+        seed: u64,
+    ) -> Result<WebGame, JsError>
+    {
+        let p1 = new_player("You", left, right).map_err(|e| JsError::new(&e))?;
+        let p2
+            = new_player("CPU", cpu_left, cpu_right)
+            .map_err(|e| JsError::new(&e))?;
+        let mut rng = StdRng::seed_from_u64(seed);
+        let rolls = Wheel::gen_rolls(&mut rng);
+        let spins_left = p1.get_handicap().get_respins();
+        Ok(Self {
+            game: Game::new(p1, p2),
+            rng,
+            rolls,
+            locks: [false; 5],
+            spins_left,
+            cpu_rolls: None,
+            winner: None,
+        })
+    }
+
+    /// The board, your rolls and locks, and the winner if the game is over,
+    /// as a JSON string.
+    pub fn state(&self) -> String {
+        json!({
+            "turn": self.game.get_turn(),
+            "maxExp": MAX_EXP,
+            "maxLevel": MAX_LEVEL,
+            "players": [
+                player_json(self.game.get_player(PlayerPos::P1)),
+                player_json(self.game.get_player(PlayerPos::P2)),
+            ],
+            "rolls": rolls_json(&self.rolls),
+            "locks": self.locks,
+            "spinsLeft": self.spins_left,
+            "cpuRolls": self.cpu_rolls.as_ref().map(rolls_json),
+            "winner": self.winner.map(winner_json),
+        })
+        .to_string()
+    }
+
+    /// Lock or unlock wheel `k`, counting from 0, for the next re-spin.
+    #[wasm_bindgen(js_name = toggleLock)]
+    pub fn toggle_lock(&mut self, k: usize) -> Result<(), JsError> {
+        self.check_playing()?;
+        if self.spins_left == 0 {
+            return Err(JsError::new("no re-spins left this turn"));
+        }
+        let lock
+            = self.locks.get_mut(k)
+            .ok_or_else(|| JsError::new(&format!("no wheel {}", k)))?;
+        *lock = !*lock;
+        Ok(())
+    }
+
+    /// Spin every unlocked wheel again.
+    pub fn respin(&mut self) -> Result<(), JsError> {
+        self.check_playing()?;
+        if self.spins_left == 0 {
+            return Err(JsError::new("no re-spins left this turn"));
+        }
+        Wheel::gen_rolls_locked(&mut self.rolls, &self.locks, &mut self.rng);
+        self.spins_left -= 1;
+        Ok(())
+    }
+
+    /// Resolve the turn with your current rolls against the computer's, then
+    /// spin for the next turn. Returns a message for every action taken.
+    #[wasm_bindgen(js_name = doTurn)]
+    pub fn do_turn(&mut self) -> Result<Vec<String>, JsError> {
+        self.check_playing()?;
+        let cpu_rolls
+            = self.game.get_rolls_strategy(
+                PlayerPos::P2, Strategy::Classic, &mut self.rng);
+        let (winner, log)
+            = self.game.do_turn_rng(&self.rolls, &cpu_rolls, &mut self.rng);
+        self.cpu_rolls = Some(cpu_rolls);
+        self.winner = winner;
+        self.rolls = Wheel::gen_rolls(&mut self.rng);
+        self.locks = [false; 5];
+        self.spins_left
+            = self.game.get_player(PlayerPos::P1).get_handicap().get_respins();
+        Ok(log.iter().map(|action| action.msg(&self.game)).collect())
+    }
+
+    fn check_playing(&self) -> Result<(), JsError> {
+        match self.winner {
+            Some(_) => Err(JsError::new("the game is over")),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,72 @@
+// Headless test of the wasm build in Node: `node web/test.mjs` after
+// `web/build.sh`.
+
+import assert from "node:assert/strict";
+import { readFileSync } from "node:fs";
+import { initSync, WebGame } from "./pkg/wheels_web.js";
+
+initSync({
+  module: readFileSync(new URL("./pkg/wheels_web_bg.wasm", import.meta.url)),
+});
+
+// Play a game to the end, locking every square and returning every message.
+function playOut(seed) {
+  const game = new WebGame("Warrior", "Mage", "Priest", "Archer", seed);
+  const messages = [];
+  for (let turn = 0; turn < 1000; turn++) {
+    let state = JSON.parse(game.state());
+    if (state.winner !== null) { return { state, messages }; }
+    while (state.spinsLeft > 0) {
+      state.rolls.forEach((panel, k) => {
+        if (panel.startsWith("S") !== state.locks[k]) { game.toggleLock(k); }
+      });
+      game.respin();
+      state = JSON.parse(game.state());
+    }
+    messages.push(...game.doTurn());
+  }
+  assert.fail("game did not end");
+}
+
+const game = new WebGame("Warrior", "Mage", "Priest", "Archer", 1n);
+const state = JSON.parse(game.state());
+assert.equal(state.turn, 0);
+assert.equal(state.players[0].name, "You");
+assert.deepEqual(state.players[1].heroes.map((hero) => hero.kind),
+  ["Priest", "Archer"]);
+assert.equal(state.players[0].crown, 10);
+assert.equal(state.rolls.length, 5);
+assert.deepEqual(state.locks, [false, false, false, false, false]);
+assert.equal(state.spinsLeft, 2);
+assert.equal(state.winner, null);
+
+game.toggleLock(3);
+assert.deepEqual(JSON.parse(game.state()).locks,
+  [false, false, false, true, false]);
+const locked = JSON.parse(game.state()).rolls[3];
+game.respin();
+game.respin();
+assert.equal(JSON.parse(game.state()).rolls[3], locked);
+assert.throws(() => game.respin(), /no re-spins left/);
+assert.throws(() => game.toggleLock(0), /no re-spins left/);
+
+game.doTurn();
+const next = JSON.parse(game.state());
+assert.equal(next.turn, 1);
+assert.equal(next.spinsLeft, 2);
+assert.equal(next.cpuRolls.length, 5);
+
+assert.throws(() => new WebGame("Warrior", "Warrior", "Mage", "Priest", 1n),
+  /two Warriors/);
+assert.throws(() => new WebGame("Warrior", "Bard", "Mage", "Priest", 1n),
+  /unknown hero 'Bard'/);
+
+const first = playOut(7n);
+const again = playOut(7n);
+assert.notEqual(first.state.winner, null);
+assert.deepEqual(again, first);
+assert.throws(() => new WebGame("Warrior", "Mage", "Priest", "Archer", 7n)
+  .toggleLock(5), /no wheel 5/);
+
+console.log(`ok: game ended on turn ${first.state.turn}, `
+  + `${first.state.winner.reason}`);